@@ -176,12 +176,24 @@ impl ApplicationHandler for App {
                     }
                 }
             },
+            WindowEvent::MouseInput { device_id: _, state, button } => {
+                if !DEBUG_RENDER {
+                    let game = self.game.as_mut().unwrap();
+                    game.handle_mouse_button(button, state);
+                }
+            },
             WindowEvent::CursorMoved { device_id, position } => {
                 if !DEBUG_RENDER {
                     let game = self.game.as_mut().unwrap();
                     game.update_cursor(position.x, position.y);
                 }
             },
+            WindowEvent::Focused(focused) => {
+                if !DEBUG_RENDER {
+                    let game = self.game.as_mut().unwrap();
+                    game.handle_focus(focused);
+                }
+            },
             _ => { },
         } 
     }
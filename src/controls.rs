@@ -0,0 +1,80 @@
+// Paddle controllers for people: their bindings through `HumanController`, or the mouse through
+// `sim::FollowController`. The game and replays both build them here so a replay drives the
+// paddles exactly the way the match it was recorded from did.
+use crate::core::action::{Action, ActionMap, InputConfig};
+use crate::core::input::InputState;
+use crate::sim::{FollowController, MatchView, PaddleController, PaddleIntent};
+
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+/// Fastest a mouse driven paddle moves, as a fraction of its full speed.
+pub const MOUSE_MAX_SPEED: f32 = 1.0;
+/// Seconds a mouse driven paddle takes to catch up with the cursor, 0 for no smoothing.
+pub const MOUSE_SMOOTHING: f32 = 0.03;
+
+/// What the human paddles read. Whoever runs the match refreshes it once a frame, before the
+/// frame's ticks.
+#[derive(Clone, Default)]
+pub struct SharedInput {
+    state: Rc<RefCell<InputState>>,
+    cursor_y: Rc<Cell<Option<f32>>>, // court height under the cursor, for the mouse paddle
+}
+
+impl SharedInput {
+    pub fn new() -> Self {
+        SharedInput::default()
+    }
+
+    pub fn set_state(&self, state: InputState) {
+        self.state.replace(state);
+    }
+
+    pub fn set_cursor_y(&self, cursor_y: Option<f32>) {
+        self.cursor_y.set(cursor_y);
+    }
+
+    /// A controller for human player `player` when `players` people are playing: the mouse if
+    /// `mouse` is set, otherwise their bindings in `controls`.
+    pub fn controller(&self, controls: &InputConfig, players: u32, player: usize, mouse: bool) -> Box<dyn PaddleController> {
+        if mouse {
+            let follow = FollowController::new(self.cursor_y.clone())
+                .with_max_speed(MOUSE_MAX_SPEED)
+                .with_smoothing(MOUSE_SMOOTHING);
+            Box::new(follow)
+        } else {
+            let actions = controls.player_map(player, players);
+            Box::new(HumanController::new(actions, self.state.clone()))
+        }
+    }
+}
+
+/// Moves a paddle with whatever is bound to `Action::MoveUp` and `Action::MoveDown`. Reads the
+/// input state the game shares with it, which is refreshed once a frame.
+pub struct HumanController {
+    actions: ActionMap,
+    input: Rc<RefCell<InputState>>,
+}
+
+impl HumanController {
+    pub fn new(actions: ActionMap, input: Rc<RefCell<InputState>>) -> Self {
+        HumanController { actions, input }
+    }
+}
+
+impl PaddleController for HumanController {
+    fn intent(&mut self, _delta: f32, _view: &MatchView) -> PaddleIntent {
+        // Keys and buttons come out as 0 or 1, sticks anywhere in between.
+        let input = self.input.borrow();
+        let axis = self.actions.value(Action::MoveUp, &input) - self.actions.value(Action::MoveDown, &input);
+        if axis <= -1.0f32 {
+            PaddleIntent::Down
+        } else if axis >= 1.0f32 {
+            PaddleIntent::Up
+        } else if axis != 0.0f32 {
+            PaddleIntent::Axis(axis)
+        } else {
+            PaddleIntent::Idle
+        }
+    }
+}
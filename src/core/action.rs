@@ -0,0 +1,539 @@
+// Actions the game cares about (move up, pause, ...) and which inputs trigger them. Bindings are
+// saved in a plain text file so they can be changed in game or by hand:
+//
+//     [global]
+//     Pause = key:Space
+//     Confirm = key:Enter, pad1:South
+//
+//     [player1]
+//     MoveUp = physical:Q, physical:K, pad1:DPadUp
+//
+// Sections are `[global]`, `[player1]` and `[player2]`. Each line binds an action to a comma
+// separated list of inputs, any of which triggers it:
+//
+//     key:<Key>        the key that types <Key>, e.g. key:Q or key:ArrowUp
+//     physical:<Key>   the key in <Key>'s spot on a US layout, whatever the actual layout
+//     mouse:<Button>   Left, Right, Middle, Back or Forward
+//     pad<N>:<Button>  a button on gamepad N, counting from 1, e.g. pad1:South
+//     pad<N>:<Axis>+   a stick or trigger on gamepad N pushed the positive way, e.g.
+//                      pad1:LeftStickY+
+//     pad<N>:<Axis>-   the same pushed the negative way; sticks are positive up and right
+//
+// Key names are `KeyKind` variants, button names `GamepadButton` variants and axis names
+// `GamepadAxis` variants; none of the names are case sensitive. An action with nothing after
+// the `=` is unbound, an action that isn't listed keeps its default. Blank lines and lines
+// starting with `#` are ignored.
+use super::input::{GamepadAxis, GamepadButton, InputState, KeyBinding, KeyKind, MouseButton};
+
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+
+named_enum! {
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+    pub enum Action {
+        MoveUp,
+        MoveDown,
+        Pause,
+        Confirm,
+        Back,
+        Serve, // skips the countdown when it's your serve
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Binding {
+    Key(KeyBinding),
+    Mouse(MouseButton),
+    Gamepad(usize, GamepadButton), // pad index from 0, button
+    GamepadAxis(usize, GamepadAxis, bool), // pad index from 0, axis, whether it's the positive side
+}
+
+/// How far an axis has to be pushed to count as down.
+pub const AXIS_THRESHOLD: f32 = 0.5;
+
+impl Binding {
+    pub fn is_down(&self, input: &InputState) -> bool {
+        match self {
+            Binding::Key(key) => input.is_key_pressed(*key),
+            Binding::Mouse(button) => input.mouse().is_down(*button as usize),
+            Binding::Gamepad(pad, button) => input.gamepad(*pad)
+                .is_some_and(|pad| pad.is_down(*button)),
+            Binding::GamepadAxis(..) => self.value(input) >= AXIS_THRESHOLD,
+        }
+    }
+
+    /// How far the binding is pushed, from 0 to 1. Keys and buttons are either 0 or 1.
+    pub fn value(&self, input: &InputState) -> f32 {
+        match self {
+            Binding::GamepadAxis(pad, axis, positive) => input.gamepad(*pad)
+                .map_or(0.0f32, |pad| axis_side(pad.axis(*axis), *positive)),
+            _ if self.is_down(input) => 1.0f32,
+            _ => 0.0f32,
+        }
+    }
+
+    // Whether an axis binding was past the threshold at the end of the last frame.
+    fn was_down(&self, input: &InputState) -> bool {
+        match self {
+            Binding::GamepadAxis(pad, axis, positive) => input.gamepad(*pad)
+                .is_some_and(|pad| {
+                    axis_side(pad.previous_axis(*axis), *positive) >= AXIS_THRESHOLD
+                }),
+            _ => false,
+        }
+    }
+
+    pub fn just_pressed(&self, input: &InputState) -> bool {
+        match self {
+            Binding::Key(key) => input.just_pressed(*key),
+            Binding::Mouse(button) => input.mouse().just_pressed(*button as usize),
+            Binding::Gamepad(pad, button) => input.gamepad(*pad)
+                .is_some_and(|pad| pad.just_pressed(*button)),
+            Binding::GamepadAxis(..) => self.is_down(input) && !self.was_down(input),
+        }
+    }
+
+    pub fn just_released(&self, input: &InputState) -> bool {
+        match self {
+            Binding::Key(key) => input.just_released(*key),
+            Binding::Mouse(button) => input.mouse().just_released(*button as usize),
+            Binding::Gamepad(pad, button) => input.gamepad(*pad)
+                .is_some_and(|pad| pad.just_released(*button)),
+            Binding::GamepadAxis(..) => !self.is_down(input) && self.was_down(input),
+        }
+    }
+
+    /// Everything that went down this frame, for picking a new binding. Letters, digits and
+    /// punctuation come back as physical keys so they stay put on other layouts.
+    pub fn pressed(input: &InputState) -> Vec<Binding> {
+        let mut pressed = Vec::new();
+        for event in input.events().iter().filter(|event| event.pressed) {
+            let binding = match (event.key, event.physical) {
+                (_, Some(physical)) if is_typed(physical) => KeyBinding::Physical(physical),
+                (Some(key), _) => KeyBinding::Logical(key),
+                (None, Some(physical)) => KeyBinding::Physical(physical),
+                (None, None) => continue,
+            };
+            pressed.push(Binding::Key(binding));
+        }
+        for button in MouseButton::ALL {
+            if input.mouse().just_pressed(*button as usize) {
+                pressed.push(Binding::Mouse(*button));
+            }
+        }
+        for (pad, state) in input.gamepads().iter().enumerate() {
+            for button in GamepadButton::ALL {
+                if state.just_pressed(*button) {
+                    pressed.push(Binding::Gamepad(pad, *button));
+                }
+            }
+            for axis in GamepadAxis::ALL {
+                for positive in [true, false] {
+                    let binding = Binding::GamepadAxis(pad, *axis, positive);
+                    if binding.just_pressed(input) {
+                        pressed.push(binding);
+                    }
+                }
+            }
+        }
+        pressed
+    }
+
+    pub fn parse(text: &str) -> Result<Binding, String> {
+        let (kind, name) = text.trim().split_once(':')
+            .ok_or_else(|| format!("expected <kind>:<name>, got \"{}\"", text.trim()))?;
+        let (kind, name) = (kind.trim().to_ascii_lowercase(), name.trim());
+
+        match kind.as_str() {
+            "key" | "physical" => KeyBinding::parse(text).map(Binding::Key),
+            "mouse" => MouseButton::from_name(name)
+                .map(Binding::Mouse)
+                .ok_or_else(|| format!("unknown mouse button \"{}\"", name)),
+            _ => {
+                let pad = kind.strip_prefix("pad")
+                    .and_then(|pad| pad.parse::<usize>().ok())
+                    .filter(|pad| *pad >= 1)
+                    .ok_or_else(|| format!("unknown input kind \"{}\"", kind))?;
+                if let Some((name, positive)) = name.strip_suffix('+').map(|name| (name, true))
+                    .or_else(|| name.strip_suffix('-').map(|name| (name, false))) {
+                    return GamepadAxis::from_name(name.trim())
+                        .map(|axis| Binding::GamepadAxis(pad - 1, axis, positive))
+                        .ok_or_else(|| format!("unknown gamepad axis \"{}\"", name.trim()));
+                }
+                GamepadButton::from_name(name)
+                    .map(|button| Binding::Gamepad(pad - 1, button))
+                    .ok_or_else(|| format!("unknown gamepad button \"{}\"", name))
+            },
+        }
+    }
+}
+
+// How far `value` is along one side of an axis, 0 when it's on the other side.
+fn axis_side(value: f32, positive: bool) -> f32 {
+    if positive { value.max(0.0f32) } else { (-value).max(0.0f32) }
+}
+
+// Keys that type a character, which depends on the layout.
+fn is_typed(key: KeyKind) -> bool {
+    key.name().len() == 1 || key.name().starts_with("Digit") || matches!(key,
+        KeyKind::Minus | KeyKind::Equal | KeyKind::Comma | KeyKind::Period | KeyKind::Slash |
+        KeyKind::Semicolon | KeyKind::Quote | KeyKind::BracketLeft | KeyKind::BracketRight |
+        KeyKind::Backslash | KeyKind::Backquote)
+}
+
+impl fmt::Display for Binding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Binding::Key(key) => write!(f, "{}", key),
+            Binding::Mouse(button) => write!(f, "mouse:{}", button.name()),
+            Binding::Gamepad(pad, button) => write!(f, "pad{}:{}", pad + 1, button.name()),
+            Binding::GamepadAxis(pad, axis, positive) => {
+                write!(f, "pad{}:{}{}", pad + 1, axis.name(), if *positive { '+' } else { '-' })
+            },
+        }
+    }
+}
+
+/// Which inputs trigger which actions. An action can have any number of bindings.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ActionMap {
+    bindings: HashMap<Action, Vec<Binding>>,
+}
+
+impl ActionMap {
+    pub fn new() -> Self {
+        ActionMap::default()
+    }
+
+    pub fn with(mut self, action: Action, bindings: &[Binding]) -> Self {
+        for binding in bindings {
+            self.bind(action, *binding);
+        }
+        self
+    }
+
+    pub fn bindings(&self, action: Action) -> &[Binding] {
+        self.bindings.get(&action).map_or(&[], Vec::as_slice)
+    }
+
+    /// Adds `binding` to `action`, if it isn't there already.
+    pub fn bind(&mut self, action: Action, binding: Binding) {
+        let bindings = self.bindings.entry(action).or_default();
+        if !bindings.contains(&binding) {
+            bindings.push(binding);
+        }
+    }
+
+    pub fn unbind(&mut self, action: Action, binding: Binding) {
+        if let Some(bindings) = self.bindings.get_mut(&action) {
+            bindings.retain(|bound| *bound != binding);
+        }
+    }
+
+    /// Replaces all of `action`'s bindings with `binding`.
+    pub fn rebind(&mut self, action: Action, binding: Binding) {
+        self.bindings.insert(action, vec![binding]);
+    }
+
+    pub fn clear(&mut self, action: Action) {
+        self.bindings.insert(action, Vec::new());
+    }
+
+    /// Every binding from `self` and `other`.
+    pub fn merged(&self, other: &ActionMap) -> ActionMap {
+        let mut merged = self.clone();
+        for (action, bindings) in &other.bindings {
+            for binding in bindings {
+                merged.bind(*action, *binding);
+            }
+        }
+        merged
+    }
+
+    pub fn is_down(&self, action: Action, input: &InputState) -> bool {
+        self.bindings(action).iter().any(|binding| binding.is_down(input))
+    }
+
+    pub fn just_pressed(&self, action: Action, input: &InputState) -> bool {
+        self.bindings(action).iter().any(|binding| binding.just_pressed(input))
+    }
+
+    pub fn just_released(&self, action: Action, input: &InputState) -> bool {
+        self.bindings(action).iter().any(|binding| binding.just_released(input))
+    }
+
+    /// How far `action` is pushed, from 0 to 1: the furthest of any of its bindings.
+    pub fn value(&self, action: Action, input: &InputState) -> f32 {
+        self.bindings(action).iter().map(|binding| binding.value(input)).fold(0.0f32, f32::max)
+    }
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Io(std::io::Error),
+    Parse { line: usize, message: String }, // line counts from 1
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Io(err) => write!(f, "{}", err),
+            ConfigError::Parse { line, message } => write!(f, "line {}: {}", line, message),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+impl From<std::io::Error> for ConfigError {
+    fn from(err: std::io::Error) -> Self {
+        ConfigError::Io(err)
+    }
+}
+
+/// All the bindings: menu and pause actions in `global`, paddle actions per player.
+#[derive(Clone, Debug, PartialEq)]
+pub struct InputConfig {
+    pub global: ActionMap,
+    pub players: [ActionMap; 2],
+}
+
+impl Default for InputConfig {
+    fn default() -> Self {
+        use KeyBinding::{Logical, Physical};
+        let key = |key| Binding::Key(Logical(key));
+        let physical = |key| Binding::Key(Physical(key));
+        let button = |pad, button| Binding::Gamepad(pad, button);
+        let stick = |pad, positive| Binding::GamepadAxis(pad, GamepadAxis::LeftStickY, positive);
+
+        let global = ActionMap::new()
+            .with(Action::Pause, &[key(KeyKind::Space), button(0, GamepadButton::Start)])
+            .with(Action::Confirm, &[
+                key(KeyKind::Enter), key(KeyKind::ArrowRight), button(0, GamepadButton::South),
+            ])
+            .with(Action::Back, &[
+                key(KeyKind::ArrowLeft), key(KeyKind::Backspace), button(0, GamepadButton::East),
+            ]);
+        let player1 = ActionMap::new()
+            .with(Action::MoveUp, &[
+                physical(KeyKind::Q), physical(KeyKind::K),
+                button(0, GamepadButton::DPadUp), stick(0, true),
+            ])
+            .with(Action::MoveDown, &[
+                physical(KeyKind::A), physical(KeyKind::J),
+                button(0, GamepadButton::DPadDown), stick(0, false),
+            ])
+            .with(Action::Serve, &[physical(KeyKind::D), button(0, GamepadButton::West)]);
+        let player2 = ActionMap::new()
+            .with(Action::MoveUp, &[
+                key(KeyKind::ArrowUp), button(1, GamepadButton::DPadUp), stick(1, true),
+            ])
+            .with(Action::MoveDown, &[
+                key(KeyKind::ArrowDown), button(1, GamepadButton::DPadDown), stick(1, false),
+            ])
+            .with(Action::Serve, &[physical(KeyKind::Slash), button(1, GamepadButton::West)]);
+
+        InputConfig {
+            global,
+            players: [player1, player2],
+        }
+    }
+}
+
+impl InputConfig {
+    /// The bindings for the paddle of player `player` when `humans` people are playing. On your
+    /// own you get both players' bindings.
+    pub fn player_map(&self, player: usize, humans: u32) -> ActionMap {
+        if humans == 1 {
+            self.players[0].merged(&self.players[1])
+        } else {
+            self.players[player].clone()
+        }
+    }
+
+    /// The bindings for player `player`, or the global ones for `None`.
+    pub fn map(&self, player: Option<usize>) -> &ActionMap {
+        match player {
+            Some(player) => &self.players[player],
+            None => &self.global,
+        }
+    }
+
+    pub fn map_mut(&mut self, player: Option<usize>) -> &mut ActionMap {
+        match player {
+            Some(player) => &mut self.players[player],
+            None => &mut self.global,
+        }
+    }
+
+    /// Reads bindings in the format described at the top of this file. Anything the text
+    /// doesn't mention keeps its default.
+    pub fn parse(text: &str) -> Result<InputConfig, ConfigError> {
+        let mut config = InputConfig::default();
+        let mut section: Option<&mut ActionMap> = None;
+
+        for (i, line) in text.lines().enumerate() {
+            let error = |message: String| ConfigError::Parse { line: i + 1, message };
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            if let Some(name) = line.strip_prefix('[').and_then(|line| line.strip_suffix(']')) {
+                section = Some(match name.trim() {
+                    "global" => &mut config.global,
+                    "player1" => &mut config.players[0],
+                    "player2" => &mut config.players[1],
+                    name => return Err(error(format!("unknown section [{}]", name))),
+                });
+                continue;
+            }
+
+            let Some((action, bindings)) = line.split_once('=') else {
+                return Err(error(format!("expected <action> = <bindings>, got \"{}\"", line)));
+            };
+            let action = Action::from_name(action.trim())
+                .ok_or_else(|| error(format!("unknown action \"{}\"", action.trim())))?;
+            let map = section.as_deref_mut()
+                .ok_or_else(|| error("binding outside of a section".to_string()))?;
+
+            map.clear(action);
+            for binding in bindings.split(',').filter(|binding| !binding.trim().is_empty()) {
+                map.bind(action, Binding::parse(binding).map_err(error)?);
+            }
+        }
+
+        Ok(config)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<InputConfig, ConfigError> {
+        InputConfig::parse(&std::fs::read_to_string(path)?)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        std::fs::write(path, self.to_string())
+    }
+}
+
+impl fmt::Display for InputConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "# gl-pong input bindings: <action> = <input>, <input>, ...")?;
+        writeln!(f, "# Inputs are key:<Key>, physical:<Key>, mouse:<Button>, pad<N>:<Button> \
+            or pad<N>:<Axis>+/-.")?;
+
+        let sections = [
+            ("global", &self.global),
+            ("player1", &self.players[0]),
+            ("player2", &self.players[1]),
+        ];
+        for (name, map) in sections {
+            writeln!(f)?;
+            writeln!(f, "[{}]", name)?;
+            for action in Action::ALL {
+                if !map.bindings.contains_key(action) {
+                    continue;
+                }
+                let bindings: Vec<_> = map.bindings(*action).iter()
+                    .map(Binding::to_string)
+                    .collect();
+                writeln!(f, "{} = {}", action.name(), bindings.join(", "))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bindings_round_trip() {
+        let bindings = [
+            Binding::Key(KeyBinding::Logical(KeyKind::ArrowUp)),
+            Binding::Key(KeyBinding::Physical(KeyKind::Q)),
+            Binding::Mouse(MouseButton::Left),
+            Binding::Gamepad(1, GamepadButton::DPadDown),
+            Binding::GamepadAxis(0, GamepadAxis::LeftStickY, false),
+            Binding::GamepadAxis(1, GamepadAxis::RightTrigger, true),
+        ];
+        for binding in bindings {
+            assert_eq!(Ok(binding), Binding::parse(&binding.to_string()));
+        }
+        assert_eq!(Ok(bindings[1]), Binding::parse(" PHYSICAL:q "));
+        assert!(Binding::parse("pad0:South").is_err());
+        assert!(Binding::parse("key:NotAKey").is_err());
+        assert!(Binding::parse("pad1:South+").is_err());
+    }
+
+    #[test]
+    fn sticks_drive_actions() {
+        use crate::core::gamepad::{MockGamepadBackend, RawGamepadEvent};
+        use crate::core::input::InputController;
+
+        let backend = MockGamepadBackend::new();
+        let mut input = InputController::new();
+        input.set_gamepad_backend(Box::new(backend.clone()));
+        let map = InputConfig::default().players[1].clone();
+
+        // Pad 2's left stick most of the way down, which the driver reports as positive.
+        backend.push(RawGamepadEvent::Axis { pad: 1, index: 1, value: 0.68 });
+        let frame = input.next_frame(0.016);
+        assert!((map.value(Action::MoveDown, &frame) - 0.6).abs() < 1e-6);
+        assert_eq!(0.0, map.value(Action::MoveUp, &frame));
+        assert!(map.just_pressed(Action::MoveDown, &frame));
+        let stick = Binding::GamepadAxis(1, GamepadAxis::LeftStickY, false);
+        assert_eq!(vec![stick], Binding::pressed(&frame));
+
+        backend.push(RawGamepadEvent::Axis { pad: 1, index: 1, value: 1.0 });
+        let frame = input.next_frame(0.016);
+        assert_eq!(1.0, map.value(Action::MoveDown, &frame));
+        assert!(map.is_down(Action::MoveDown, &frame));
+        assert!(!map.just_pressed(Action::MoveDown, &frame));
+
+        backend.push(RawGamepadEvent::Axis { pad: 1, index: 1, value: 0.0 });
+        let frame = input.next_frame(0.016);
+        assert!(map.just_released(Action::MoveDown, &frame));
+    }
+
+    #[test]
+    fn default_player_bindings_stay_clear_of_global_ones() {
+        let config = InputConfig::default();
+        for map in &config.players {
+            for action in Action::ALL {
+                for binding in map.bindings(*action) {
+                    let clash = Action::ALL.iter()
+                        .find(|global| config.global.bindings(**global).contains(binding));
+                    assert_eq!(None, clash, "{} is bound to {:?} too", binding, action);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn config_round_trips_through_text() {
+        let mut config = InputConfig::default();
+        config.players[1].rebind(Action::MoveUp, Binding::Mouse(MouseButton::Right));
+        config.global.clear(Action::Back);
+        assert_eq!(config, InputConfig::parse(&config.to_string()).unwrap());
+    }
+
+    #[test]
+    fn parse_keeps_defaults_and_reports_lines() {
+        let config = InputConfig::parse("# comment\n\n[player1]\nmoveup = key:W\n").unwrap();
+        let defaults = InputConfig::default();
+        let w = Binding::Key(KeyBinding::Logical(KeyKind::W));
+        assert_eq!(&[w], config.players[0].bindings(Action::MoveUp));
+        assert_eq!(
+            defaults.players[0].bindings(Action::MoveDown),
+            config.players[0].bindings(Action::MoveDown)
+        );
+        assert_eq!(defaults.global, config.global);
+
+        match InputConfig::parse("[global]\nPause = key:Space\nJump = key:W\n") {
+            Err(ConfigError::Parse { line, .. }) => assert_eq!(3, line),
+            other => panic!("expected a parse error, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,303 @@
+// Gamepads. A `GamepadBackend` reports raw button and axis numbers from whatever the platform
+// gives us, and a `GamepadMapping` turns those into named buttons and axes with deadzones
+// applied, which `InputController` then tracks like any other input.
+use std::cell::RefCell;
+use std::collections::{HashMap, VecDeque};
+use std::rc::Rc;
+
+named_enum! {
+    /// Buttons by position, so South is A on an Xbox pad and Cross on a PlayStation one.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+    pub enum GamepadButton {
+        South,
+        East,
+        West,
+        North,
+        LeftShoulder,
+        RightShoulder,
+        LeftTrigger,
+        RightTrigger,
+        Select,
+        Start,
+        LeftStick,
+        RightStick,
+        DPadUp,
+        DPadDown,
+        DPadLeft,
+        DPadRight,
+    }
+}
+
+named_enum! {
+    /// Sticks go from -1 to 1 with up and right positive, triggers from 0 to 1.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+    pub enum GamepadAxis {
+        LeftStickX,
+        LeftStickY,
+        RightStickX,
+        RightStickY,
+        LeftTrigger,
+        RightTrigger,
+    }
+}
+
+/// Straight from the backend: pads, buttons and axes are just numbers, axes go from -1 to 1.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RawGamepadEvent {
+    Connected(usize),
+    Disconnected(usize),
+    Button { pad: usize, index: u8, pressed: bool },
+    Axis { pad: usize, index: u8, value: f32 },
+}
+
+/// After mapping: named buttons and axes, deadzones applied.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum GamepadEvent {
+    Connected(usize),
+    Disconnected(usize),
+    Button { pad: usize, button: GamepadButton, pressed: bool },
+    Axis { pad: usize, axis: GamepadAxis, value: f32 },
+}
+
+/// Where gamepad input comes from. Polled once a frame.
+pub trait GamepadBackend {
+    fn poll(&mut self) -> Vec<RawGamepadEvent>;
+}
+
+/// What a raw axis is on the pad.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AxisMapping {
+    Stick { axis: GamepadAxis, invert: bool },
+    /// Rests at -1. Also presses `button` once it's pulled past `Deadzones::trigger_press`.
+    Trigger { axis: GamepadAxis, button: GamepadButton },
+    /// A d-pad reported as an axis: -1 is `negative`, 1 is `positive`.
+    Hat { negative: GamepadButton, positive: GamepadButton },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Deadzones {
+    pub stick: f32,         // stick values closer to 0 than this read as 0
+    pub trigger: f32,       // trigger values below this read as 0
+    pub trigger_press: f32, // how far a trigger has to go to count as its button
+}
+
+impl Default for Deadzones {
+    fn default() -> Self {
+        Deadzones {
+            stick: 0.2,
+            trigger: 0.1,
+            trigger_press: 0.5,
+        }
+    }
+}
+
+/// Turns raw button and axis numbers into `GamepadButton`s and `GamepadAxis`es.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GamepadMapping {
+    buttons: HashMap<u8, GamepadButton>,
+    axes: HashMap<u8, AxisMapping>,
+    pub deadzones: Deadzones,
+}
+
+impl Default for GamepadMapping {
+    /// The layout Linux's xpad driver uses for Xbox style pads, which most pads copy.
+    fn default() -> Self {
+        use GamepadButton::*;
+        let mut mapping = GamepadMapping::new();
+        let buttons = [South, East, West, North, LeftShoulder, RightShoulder, Select, Start];
+        for (index, button) in buttons.into_iter().enumerate() {
+            mapping.map_button(index as u8, button);
+        }
+        // 8 is the guide button, which we leave to the system.
+        mapping.map_button(9, LeftStick);
+        mapping.map_button(10, RightStick);
+
+        mapping.map_axis(0, AxisMapping::Stick { axis: GamepadAxis::LeftStickX, invert: false });
+        mapping.map_axis(1, AxisMapping::Stick { axis: GamepadAxis::LeftStickY, invert: true });
+        mapping.map_axis(2, AxisMapping::Trigger { axis: GamepadAxis::LeftTrigger, button: LeftTrigger });
+        mapping.map_axis(3, AxisMapping::Stick { axis: GamepadAxis::RightStickX, invert: false });
+        mapping.map_axis(4, AxisMapping::Stick { axis: GamepadAxis::RightStickY, invert: true });
+        mapping.map_axis(5, AxisMapping::Trigger { axis: GamepadAxis::RightTrigger, button: RightTrigger });
+        mapping.map_axis(6, AxisMapping::Hat { negative: DPadLeft, positive: DPadRight });
+        mapping.map_axis(7, AxisMapping::Hat { negative: DPadUp, positive: DPadDown });
+        mapping
+    }
+}
+
+impl GamepadMapping {
+    /// A mapping that doesn't know any buttons or axes.
+    pub fn new() -> Self {
+        GamepadMapping {
+            buttons: HashMap::new(),
+            axes: HashMap::new(),
+            deadzones: Deadzones::default(),
+        }
+    }
+
+    pub fn map_button(&mut self, index: u8, button: GamepadButton) {
+        self.buttons.insert(index, button);
+    }
+
+    pub fn map_axis(&mut self, index: u8, mapping: AxisMapping) {
+        self.axes.insert(index, mapping);
+    }
+
+    /// What `raw` means on this pad. Unmapped buttons and axes come out as nothing, a trigger or
+    /// hat can come out as a few events.
+    pub fn translate(&self, raw: RawGamepadEvent) -> Vec<GamepadEvent> {
+        match raw {
+            RawGamepadEvent::Connected(pad) => vec![GamepadEvent::Connected(pad)],
+            RawGamepadEvent::Disconnected(pad) => vec![GamepadEvent::Disconnected(pad)],
+            RawGamepadEvent::Button { pad, index, pressed } => self.buttons.get(&index)
+                .map(|button| GamepadEvent::Button { pad, button: *button, pressed })
+                .into_iter()
+                .collect(),
+            RawGamepadEvent::Axis { pad, index, value } => {
+                let Some(mapping) = self.axes.get(&index) else {
+                    return Vec::new();
+                };
+                let value = value.clamp(-1.0f32, 1.0f32);
+                match *mapping {
+                    AxisMapping::Stick { axis, invert } => {
+                        let value = if invert { -value } else { value };
+                        let value = apply_deadzone(value, self.deadzones.stick);
+                        vec![GamepadEvent::Axis { pad, axis, value }]
+                    },
+                    AxisMapping::Trigger { axis, button } => {
+                        let value = apply_deadzone((value + 1.0f32) / 2.0f32, self.deadzones.trigger);
+                        let pressed = value >= self.deadzones.trigger_press;
+                        vec![GamepadEvent::Axis { pad, axis, value }, GamepadEvent::Button { pad, button, pressed }]
+                    },
+                    AxisMapping::Hat { negative, positive } => vec![
+                        GamepadEvent::Button { pad, button: negative, pressed: value < -0.5f32 },
+                        GamepadEvent::Button { pad, button: positive, pressed: value > 0.5f32 },
+                    ],
+                }
+            },
+        }
+    }
+}
+
+// Zeroes anything inside `deadzone` and stretches the rest so the output still covers 0 to 1.
+fn apply_deadzone(value: f32, deadzone: f32) -> f32 {
+    if value.abs() <= deadzone || deadzone >= 1.0f32 {
+        return 0.0f32;
+    }
+    value.signum() * (value.abs() - deadzone) / (1.0f32 - deadzone)
+}
+
+/// A backend that plays back whatever is pushed into it, for tests. Clones share the same queue,
+/// so keep one to push into after handing the other to an `InputController`.
+#[derive(Clone, Default)]
+pub struct MockGamepadBackend {
+    queue: Rc<RefCell<VecDeque<RawGamepadEvent>>>,
+}
+
+impl MockGamepadBackend {
+    pub fn new() -> Self {
+        MockGamepadBackend::default()
+    }
+
+    pub fn push(&self, event: RawGamepadEvent) {
+        self.queue.borrow_mut().push_back(event);
+    }
+}
+
+impl GamepadBackend for MockGamepadBackend {
+    fn poll(&mut self) -> Vec<RawGamepadEvent> {
+        self.queue.borrow_mut().drain(..).collect()
+    }
+}
+
+/// Pads through the Linux joystick interface (`/dev/input/js*`). Each pad is read on its own
+/// thread.
+#[cfg(target_os = "linux")]
+pub struct JoystickBackend {
+    receiver: std::sync::mpsc::Receiver<RawGamepadEvent>,
+}
+
+#[cfg(target_os = "linux")]
+impl JoystickBackend {
+    pub const MAX_PADS: usize = 4;
+
+    /// Opens whichever pads are plugged in right now.
+    pub fn open() -> Self {
+        let (sender, receiver) = std::sync::mpsc::channel();
+        for pad in 0..JoystickBackend::MAX_PADS {
+            let Ok(mut device) = std::fs::File::open(format!("/dev/input/js{}", pad)) else {
+                continue;
+            };
+            let sender = sender.clone();
+            std::thread::spawn(move || {
+                use std::io::Read;
+
+                let _ = sender.send(RawGamepadEvent::Connected(pad));
+                // struct js_event { u32 time; i16 value; u8 type; u8 number; }
+                let mut event = [0u8; 8];
+                while device.read_exact(&mut event).is_ok() {
+                    let value = i16::from_ne_bytes([event[4], event[5]]);
+                    let (kind, index) = (event[6] & !0x80, event[7]); // 0x80 marks initial state
+                    let raw = match kind {
+                        0x01 => RawGamepadEvent::Button { pad, index, pressed: value != 0 },
+                        0x02 => RawGamepadEvent::Axis { pad, index, value: value as f32 / i16::MAX as f32 },
+                        _ => continue,
+                    };
+                    if sender.send(raw).is_err() {
+                        return;
+                    }
+                }
+                let _ = sender.send(RawGamepadEvent::Disconnected(pad));
+            });
+        }
+        JoystickBackend { receiver }
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl GamepadBackend for JoystickBackend {
+    fn poll(&mut self) -> Vec<RawGamepadEvent> {
+        self.receiver.try_iter().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_mapping_names_buttons_and_axes() {
+        let mapping = GamepadMapping::default();
+        assert_eq!(
+            vec![GamepadEvent::Button { pad: 1, button: GamepadButton::Start, pressed: true }],
+            mapping.translate(RawGamepadEvent::Button { pad: 1, index: 7, pressed: true })
+        );
+        assert!(mapping.translate(RawGamepadEvent::Button { pad: 0, index: 8, pressed: true }).is_empty());
+
+        // Pushing the stick up reads as negative from the driver.
+        let up = mapping.translate(RawGamepadEvent::Axis { pad: 0, index: 1, value: -1.0 });
+        assert_eq!(vec![GamepadEvent::Axis { pad: 0, axis: GamepadAxis::LeftStickY, value: 1.0 }], up);
+
+        let hat = mapping.translate(RawGamepadEvent::Axis { pad: 0, index: 7, value: -1.0 });
+        assert!(hat.contains(&GamepadEvent::Button { pad: 0, button: GamepadButton::DPadUp, pressed: true }));
+        assert!(hat.contains(&GamepadEvent::Button { pad: 0, button: GamepadButton::DPadDown, pressed: false }));
+    }
+
+    #[test]
+    fn deadzones_zero_small_values_and_rescale() {
+        let mapping = GamepadMapping::default();
+        let stick = |value| match mapping.translate(RawGamepadEvent::Axis { pad: 0, index: 0, value })[0] {
+            GamepadEvent::Axis { value, .. } => value,
+            other => panic!("expected an axis, got {:?}", other),
+        };
+        assert_eq!(0.0, stick(0.15));
+        assert_eq!(0.0, stick(-0.2));
+        assert!((stick(0.6) - 0.5).abs() < 1e-6);
+        assert_eq!(-1.0, stick(-1.0));
+
+        // Triggers rest at -1, which is 0 once mapped.
+        let resting = mapping.translate(RawGamepadEvent::Axis { pad: 0, index: 5, value: -1.0 });
+        assert!(resting.contains(&GamepadEvent::Axis { pad: 0, axis: GamepadAxis::RightTrigger, value: 0.0 }));
+        let pulled = mapping.translate(RawGamepadEvent::Axis { pad: 0, index: 5, value: 0.5 });
+        assert!(pulled.contains(&GamepadEvent::Button { pad: 0, button: GamepadButton::RightTrigger, pressed: true }));
+    }
+}
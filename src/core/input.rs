@@ -1,115 +1,169 @@
 use winit::event::{KeyEvent, ElementState};
-use winit::keyboard::{Key, NamedKey, SmolStr};
+use winit::event::MouseButton as WinitMouseButton;
+use winit::keyboard::PhysicalKey;
 use nalgebra_glm as glm;
 
+use std::collections::HashMap;
+use std::fmt;
 
 
-pub enum KeyKind {
-    Space,
-    ArrowUp,
-    ArrowDown,
-    K,
-    J,
-    Q,
-    A,
-    Enter,
-    ArrowLeft,
-    ArrowRight,
-    W,
-    S,
-    D,
+
+pub use super::keys::{KeyBinding, KeyKind, KeySet};
+pub use super::gamepad::{GamepadAxis, GamepadButton};
+use super::gamepad::{GamepadBackend, GamepadEvent, GamepadMapping};
+
+
+
+
+named_enum! {
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+    pub enum MouseButton {
+        Left,
+        Right,
+        Middle,
+        Back,
+        Forward,
+    }
 }
 
+/// Up to 32 buttons as bits, with what changed since the last frame. Used for the mouse and for
+/// each gamepad.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ButtonState {
+    down: u32,
+    pressed: u32,  // went down since the last frame, even if already released
+    released: u32, // came up since the last frame, even if pressed again
+}
 
+impl ButtonState {
+    pub fn is_down(&self, bit: usize) -> bool {
+        self.down & (1 << bit) != 0
+    }
 
+    pub fn just_pressed(&self, bit: usize) -> bool {
+        self.pressed & (1 << bit) != 0
+    }
 
-const KEY_SPACE: u32        = 1 << 0;
-const KEY_ARROW_UP: u32     = 1 << 1;
-const KEY_ARROW_DOWN: u32   = 1 << 2;
-const KEY_K: u32            = 1 << 3;
-const KEY_J: u32            = 1 << 4;
-const KEY_Q: u32            = 1 << 5;
-const KEY_A: u32            = 1 << 6;
-const KEY_ENTER: u32        = 1 << 7;
-const KEY_W: u32            = 1 << 8;
-const KEY_S: u32            = 1 << 9;
-const KEY_D: u32            = 1 << 10;
-const KEY_ARROW_LEFT: u32   = 1 << 11;
-const KEY_ARROW_RIGHT: u32  = 1 << 12;
-
-
-
-fn mask_from_winit_key(key: winit::keyboard::Key) -> u32 {
-    match key {
-        Key::Named(NamedKey::Space) => { KEY_SPACE },
-        Key::Named(NamedKey::ArrowUp) => { KEY_ARROW_UP },
-        Key::Named(NamedKey::ArrowDown) => { KEY_ARROW_DOWN },
-        Key::Named(NamedKey::ArrowLeft) => { KEY_ARROW_LEFT },
-        Key::Named(NamedKey::ArrowRight) => { KEY_ARROW_RIGHT },
-        Key::Named(NamedKey::Enter) => { KEY_ENTER },
-        Key::Character(character) => {
-            if character == "k" || character == "K" {
-                KEY_K
-            } else if character == "j" {
-                KEY_J
-            } else if character == "q" {
-                KEY_Q
-            } else if character == "a" {
-                KEY_A
-            } else if character == "w" {
-                KEY_W
-            } else if character == "s" {
-                KEY_S
-            } else if character == "d" {
-                KEY_D
-            } else {
-                0
-            }
-        },
-        _ => { 0 }
+    pub fn just_released(&self, bit: usize) -> bool {
+        self.released & (1 << bit) != 0
+    }
+
+    // Returns whether the button actually changed.
+    fn set(&mut self, bit: usize, down: bool) -> bool {
+        let mask = 1 << bit;
+        if down == (self.down & mask != 0) {
+            return false;
+        }
+        if down {
+            self.down |= mask;
+            self.pressed |= mask;
+        } else {
+            self.down &= !mask;
+            self.released |= mask;
+        }
+        true
+    }
+
+    fn next_frame(&mut self) {
+        self.pressed = 0;
+        self.released = 0;
     }
 }
 
-fn mask_from_key_kind(key: &KeyKind) -> u32 {
-    match key {
-        KeyKind::Space => { KEY_SPACE },
-        KeyKind::ArrowUp => { KEY_ARROW_UP },
-        KeyKind::ArrowDown => { KEY_ARROW_DOWN },
-        KeyKind::K => { KEY_K },
-        KeyKind::J => { KEY_J },
-        KeyKind::Q => { KEY_Q },
-        KeyKind::A => { KEY_A },
-        KeyKind::Enter => { KEY_ENTER },
-        KeyKind::ArrowLeft => { KEY_ARROW_LEFT },
-        KeyKind::ArrowRight => { KEY_ARROW_RIGHT },
-        KeyKind::W => { KEY_W },
-        KeyKind::S => { KEY_S },
-        KeyKind::D => { KEY_D },
+
+
+
+/// One gamepad: whether it's plugged in, its buttons and where its sticks and triggers are.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct GamepadState {
+    connected: bool,
+    buttons: ButtonState,
+    axes: [f32; GamepadAxis::ALL.len()],
+    prev_axes: [f32; GamepadAxis::ALL.len()], // axes at the end of the last frame
+}
+
+impl GamepadState {
+    pub fn is_connected(&self) -> bool {
+        self.connected
+    }
+
+    pub fn buttons(&self) -> &ButtonState {
+        &self.buttons
+    }
+
+    pub fn is_down(&self, button: GamepadButton) -> bool {
+        self.buttons.is_down(button as usize)
+    }
+
+    pub fn just_pressed(&self, button: GamepadButton) -> bool {
+        self.buttons.just_pressed(button as usize)
+    }
+
+    pub fn just_released(&self, button: GamepadButton) -> bool {
+        self.buttons.just_released(button as usize)
+    }
+
+    /// Where `axis` is, deadzone already applied.
+    pub fn axis(&self, axis: GamepadAxis) -> f32 {
+        self.axes[axis as usize]
+    }
+
+    /// Where `axis` was at the end of the last frame.
+    pub fn previous_axis(&self, axis: GamepadAxis) -> f32 {
+        self.prev_axes[axis as usize]
+    }
+
+    fn next_frame(&mut self) {
+        self.buttons.next_frame();
+        self.prev_axes = self.axes;
     }
 }
 
 
 
 
-pub struct KeyMap {
-    pub move_up: Vec<KeyKind>,
-    pub move_down: Vec<KeyKind>,
+/// A key going down or coming back up, in the order it happened. `key` is what the key types
+/// and `physical` where it sits; either can be `None` for keys we don't track.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct KeyInput {
+    pub key: Option<KeyKind>,
+    pub physical: Option<KeyKind>,
+    pub pressed: bool,
 }
 
 
 
 
 pub struct InputController {
-    key_state: u32,
-    cursor_pos: glm::Vec2,
+    key_state: KeySet,
+    prev_key_state: KeySet,           // key_state at the end of the last frame
+    pressed_keys: KeySet,             // keys that went down since the last frame, even if already released
+    released_keys: KeySet,            // keys that came up since the last frame, even if pressed again
+    held_time: Vec<(KeyBinding, f32)>, // seconds each held key has been down
+    events: Vec<KeyInput>,            // everything that happened since the last frame
+    typed: HashMap<PhysicalKey, KeyKind>, // what each held key typed when it went down
+    mouse: ButtonState,
+    gamepads: Vec<GamepadState>,      // indexed by pad, grows as pads show up
+    gamepad_backend: Option<Box<dyn GamepadBackend>>,
+    gamepad_mapping: GamepadMapping,
+    cursor_pos: Option<glm::Vec2>,     // window pixels from the top left, `None` until it moves
 }
 
 
 
 
+/// The input for one frame: which keys are down, what changed since the last frame and for how
+/// long keys have been held.
+#[derive(Clone, Debug, Default, PartialEq)]
 pub struct InputState {
-    key_state: u32,
-    cursor_pos: glm::Vec2,
+    key_state: KeySet,
+    pressed_keys: KeySet,
+    released_keys: KeySet,
+    held_time: Vec<(KeyBinding, f32)>,
+    events: Vec<KeyInput>,
+    mouse: ButtonState,
+    gamepads: Vec<GamepadState>,
+    cursor_pos: Option<glm::Vec2>,
 }
 
 
@@ -118,28 +172,172 @@ pub struct InputState {
 impl InputController {
     pub fn new() -> Self {
         InputController {
-            key_state: 0,
-            cursor_pos: glm::Vec2::new(0.0, 0.0),
+            key_state: KeySet::new(),
+            prev_key_state: KeySet::new(),
+            pressed_keys: KeySet::new(),
+            released_keys: KeySet::new(),
+            held_time: Vec::new(),
+            events: Vec::new(),
+            typed: HashMap::new(),
+            mouse: ButtonState::default(),
+            gamepads: Vec::new(),
+            gamepad_backend: None,
+            gamepad_mapping: GamepadMapping::default(),
+            cursor_pos: None,
         }
     }
 
+    /// Where gamepad input comes from. Without one, pads only change through `handle_gamepad`.
+    pub fn set_gamepad_backend(&mut self, backend: Box<dyn GamepadBackend>) {
+        self.gamepad_backend = Some(backend);
+    }
+
+    /// How the backend's raw buttons and axes map to named ones, used for every pad.
+    pub fn set_gamepad_mapping(&mut self, mapping: GamepadMapping) {
+        self.gamepad_mapping = mapping;
+    }
+
+    /// The input so far this frame, without using it up. Edges and events keep piling up until
+    /// `next_frame` is called.
     pub fn state(&self) -> InputState {
-        InputState::new(self.key_state, self.cursor_pos.clone())
+        InputState {
+            key_state: self.key_state,
+            pressed_keys: self.pressed_keys,
+            released_keys: self.released_keys,
+            held_time: self.held_time.clone(),
+            events: self.events.clone(),
+            mouse: self.mouse,
+            gamepads: self.gamepads.clone(),
+            cursor_pos: self.cursor_pos,
+        }
     }
 
+    /// Ends the frame: hands back everything that happened since the last call and starts
+    /// collecting the next frame. `delta` is the frame time, used for how long keys are held.
+    /// Call this once per frame.
+    pub fn next_frame(&mut self, delta: f32) -> InputState {
+        self.poll_gamepads();
+
+        let held_before = std::mem::take(&mut self.held_time);
+        for key in self.key_state.iter() {
+            let held = held_before.iter()
+                .find(|(held, _)| *held == key)
+                .filter(|_| self.prev_key_state.contains(key) && !self.pressed_keys.contains(key))
+                .map_or(0.0f32, |(_, time)| time + delta);
+            self.held_time.push((key, held));
+        }
+
+        let state = InputState {
+            key_state: self.key_state,
+            pressed_keys: self.pressed_keys,
+            released_keys: self.released_keys,
+            held_time: self.held_time.clone(),
+            events: std::mem::take(&mut self.events),
+            mouse: self.mouse,
+            gamepads: self.gamepads.clone(),
+            cursor_pos: self.cursor_pos,
+        };
+        self.prev_key_state = self.key_state;
+        self.pressed_keys.clear();
+        self.released_keys.clear();
+        self.mouse.next_frame();
+        for pad in &mut self.gamepads {
+            pad.next_frame();
+        }
+        state
+    }
+
+    /// Records where the cursor is, in window pixels from the top left.
     pub fn handle_cursor(&mut self, x: f32, y: f32) {
-        self.cursor_pos.x = x;
-        self.cursor_pos.y = y;
+        self.cursor_pos = Some(glm::Vec2::new(x, y));
+    }
+
+    pub fn handle_mouse_button(&mut self, button: WinitMouseButton, state: ElementState) {
+        let button = match button {
+            WinitMouseButton::Left => MouseButton::Left,
+            WinitMouseButton::Right => MouseButton::Right,
+            WinitMouseButton::Middle => MouseButton::Middle,
+            WinitMouseButton::Back => MouseButton::Back,
+            WinitMouseButton::Forward => MouseButton::Forward,
+            WinitMouseButton::Other(_) => return,
+        };
+        self.mouse.set(button as usize, state.is_pressed());
+    }
+
+    fn poll_gamepads(&mut self) {
+        let Some(backend) = self.gamepad_backend.as_mut() else {
+            return;
+        };
+        for raw in backend.poll() {
+            for event in self.gamepad_mapping.translate(raw) {
+                self.handle_gamepad(event);
+            }
+        }
+    }
+
+    /// Records a gamepad event that's already been through a `GamepadMapping`.
+    pub fn handle_gamepad(&mut self, event: GamepadEvent) {
+        let pad = match event {
+            GamepadEvent::Connected(pad) | GamepadEvent::Disconnected(pad) => pad,
+            GamepadEvent::Button { pad, .. } | GamepadEvent::Axis { pad, .. } => pad,
+        };
+        if self.gamepads.len() <= pad {
+            self.gamepads.resize(pad + 1, GamepadState::default());
+        }
+        let state = &mut self.gamepads[pad];
+        match event {
+            GamepadEvent::Connected(_) => state.connected = true,
+            GamepadEvent::Disconnected(_) => {
+                // Let go of everything so nothing stays stuck down.
+                for button in GamepadButton::ALL {
+                    state.buttons.set(*button as usize, false);
+                }
+                state.axes = Default::default();
+                state.connected = false;
+            },
+            GamepadEvent::Button { button, pressed, .. } => {
+                state.connected = true;
+                state.buttons.set(button as usize, pressed);
+            },
+            GamepadEvent::Axis { axis, value, .. } => {
+                state.connected = true;
+                state.axes[axis as usize] = value;
+            },
+        }
     }
 
     pub fn handle_keyboard(&mut self, event: KeyEvent) {
-        let mask = mask_from_winit_key(event.logical_key);
-        if mask != 0 {
-            self.update_state(mask, event.state);
+        // Held keys auto-repeat; that's not a new press.
+        if event.repeat {
+            return;
+        }
+        let key = KeyKind::from_logical(&event.logical_key);
+        self.handle_key(event.physical_key, key, event.state);
+    }
+
+    fn handle_key(&mut self, physical_key: PhysicalKey, key: Option<KeyKind>, state: ElementState) {
+        // A key can type something else by the time it comes up (shift + 1 is "!"), so release
+        // whatever it typed when it went down.
+        let key = match state {
+            ElementState::Pressed => {
+                if let Some(key) = key {
+                    self.typed.insert(physical_key, key);
+                }
+                key
+            },
+            ElementState::Released => self.typed.remove(&physical_key).or(key),
+        };
+        let physical = KeyKind::from_physical(physical_key);
+
+        let logical_changed = key.is_some_and(|key| self.update_state(KeyBinding::Logical(key), state));
+        let physical_changed = physical.is_some_and(|key| self.update_state(KeyBinding::Physical(key), state));
+        if logical_changed || physical_changed {
+            self.events.push(KeyInput { key, physical, pressed: state.is_pressed() });
         }
     }
 
     /*
+     * Every key has a bit in a `KeySet`, the same as it was with the old u32 masks:
      *
      * 0b000000 => No key pressed,
      * 0b000100 => KEY_ARROW_DOWN is pressed
@@ -157,13 +355,20 @@ impl InputController {
      *      ~0b0100000 = 0b1011111;
      *      0b0100100 & 0b1011111 = 0b00000100;
      * */
-    fn update_state(&mut self, key_mask: u32, state: ElementState) {
-        // println!("KEY_STATE BEFORE: {:#8b}", self.key_state);
+    // Returns whether `key` actually went down or came up.
+    fn update_state(&mut self, key: KeyBinding, state: ElementState) -> bool {
         match state {
-            ElementState::Pressed => { self.key_state |= key_mask; },
-            ElementState::Released => { self.key_state &= !key_mask; },
+            ElementState::Pressed if !self.key_state.contains(key) => {
+                self.key_state.insert(key);
+                self.pressed_keys.insert(key);
+            },
+            ElementState::Released if self.key_state.contains(key) => {
+                self.key_state.remove(key);
+                self.released_keys.insert(key);
+            },
+            _ => { return false; },
         }
-        // println!("KEY_STATE AFTER: {:#8b}", self.key_state);
+        true
     }
 }
 
@@ -171,26 +376,281 @@ impl InputController {
 
 
 impl InputState {
-    fn new(key_state: u32, cursor_pos: glm::Vec2) -> Self {
-        InputState {
-            key_state,
-            cursor_pos,
-        }
+    /// Whether `key` is down. Takes a `KeyKind` for what a key types or a `KeyBinding` to ask
+    /// about where it sits.
+    pub fn is_key_pressed(&self, key: impl Into<KeyBinding>) -> bool {
+        self.key_state.contains(key)
     }
 
-    pub fn is_key_pressed(&self, key: &KeyKind) -> bool {
-        let mask = mask_from_key_kind(key);
-        self.key_state & mask > 0
-    }
-
-    pub fn any_pressed(&self, keys: &[KeyKind]) -> bool {
+    pub fn any_pressed(&self, keys: &[KeyBinding]) -> bool {
         for key in keys {
-            if self.is_key_pressed(key) {
+            if self.is_key_pressed(*key) {
                 return true;
             }
         }
 
         false
     }
+
+    /// Whether `key` went down this frame. A tap that was released again before the frame ended
+    /// still counts.
+    pub fn just_pressed(&self, key: impl Into<KeyBinding>) -> bool {
+        self.pressed_keys.contains(key)
+    }
+
+    /// Whether `key` came up this frame.
+    pub fn just_released(&self, key: impl Into<KeyBinding>) -> bool {
+        self.released_keys.contains(key)
+    }
+
+    /// Seconds `key` has been held, not counting the frame it went down in. Zero when it's up.
+    pub fn held_duration(&self, key: impl Into<KeyBinding>) -> f32 {
+        let key = key.into();
+        self.held_time.iter()
+            .find(|(held, _)| *held == key)
+            .map_or(0.0f32, |(_, time)| *time)
+    }
+
+    /// Every press and release since the last frame, oldest first.
+    pub fn events(&self) -> &[KeyInput] {
+        &self.events
+    }
+
+    pub fn mouse(&self) -> &ButtonState {
+        &self.mouse
+    }
+
+    /// Where the cursor is, in window pixels from the top left, or `None` if it hasn't moved
+    /// over the window yet. `OrthographicCamera::screen_to_world` takes it from there.
+    pub fn cursor(&self) -> Option<glm::Vec2> {
+        self.cursor_pos
+    }
+
+    /// Gamepad `pad`, or `None` if we've never heard from it.
+    pub fn gamepad(&self, pad: usize) -> Option<&GamepadState> {
+        self.gamepads.get(pad)
+    }
+
+    pub fn gamepads(&self) -> &[GamepadState] {
+        &self.gamepads
+    }
+}
+
+
+
+
+// Written as one line of `name=value` fields, for replays. Held times and the event list are
+// left out, so a state read back has neither.
+impl fmt::Display for InputState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn keys(set: &KeySet) -> String {
+            set.iter().map(|key| key.to_string()).collect::<Vec<_>>().join(",")
+        }
+        fn buttons(state: &ButtonState) -> String {
+            format!("{:x}/{:x}/{:x}", state.down, state.pressed, state.released)
+        }
+        fn axes(axes: &[f32]) -> String {
+            axes.iter().map(f32::to_string).collect::<Vec<_>>().join(",")
+        }
+
+        let mut fields = Vec::new();
+        let key_sets = [("keys", &self.key_state), ("pressed", &self.pressed_keys), ("released", &self.released_keys)];
+        for (name, set) in key_sets {
+            if !set.is_empty() {
+                fields.push(format!("{}={}", name, keys(set)));
+            }
+        }
+        if self.mouse != ButtonState::default() {
+            fields.push(format!("mouse={}", buttons(&self.mouse)));
+        }
+        for (i, pad) in self.gamepads.iter().enumerate() {
+            fields.push(format!("pad{}={}/{}/{}/{}", i, pad.connected as u8, buttons(&pad.buttons),
+                axes(&pad.axes), axes(&pad.prev_axes)));
+        }
+        if let Some(cursor) = self.cursor_pos {
+            fields.push(format!("cursor={},{}", cursor.x, cursor.y));
+        }
+        write!(f, "{}", fields.join(" "))
+    }
+}
+
+impl InputState {
+    /// This state without held times and events, which is all that `to_string` writes out.
+    pub fn snapshot(&self) -> InputState {
+        InputState {
+            held_time: Vec::new(),
+            events: Vec::new(),
+            ..self.clone()
+        }
+    }
+
+    /// Reads back a state written with `to_string`.
+    pub fn parse(text: &str) -> Result<InputState, String> {
+        fn number<T: std::str::FromStr>(text: &str) -> Result<T, String> {
+            text.trim().parse().map_err(|_| format!("bad number \"{}\"", text))
+        }
+        fn keys(text: &str) -> Result<KeySet, String> {
+            let mut set = KeySet::new();
+            for key in text.split(',') {
+                set.insert(KeyBinding::parse(key)?);
+            }
+            Ok(set)
+        }
+        fn buttons(parts: &[&str]) -> Result<ButtonState, String> {
+            let bits = |text: &str| u32::from_str_radix(text, 16).map_err(|_| format!("bad button bits \"{}\"", text));
+            match parts {
+                [down, pressed, released] => Ok(ButtonState { down: bits(down)?, pressed: bits(pressed)?, released: bits(released)? }),
+                _ => Err(format!("expected <down>/<pressed>/<released>, got \"{}\"", parts.join("/"))),
+            }
+        }
+        fn axes(text: &str) -> Result<[f32; GamepadAxis::ALL.len()], String> {
+            let values = text.split(',').map(number).collect::<Result<Vec<f32>, _>>()?;
+            values.try_into().map_err(|_| format!("expected {} axes, got \"{}\"", GamepadAxis::ALL.len(), text))
+        }
+
+        let mut state = InputState::default();
+        for field in text.split_whitespace() {
+            let (name, value) = field.split_once('=').ok_or_else(|| format!("expected <name>=<value>, got \"{}\"", field))?;
+            match name {
+                "keys" => state.key_state = keys(value)?,
+                "pressed" => state.pressed_keys = keys(value)?,
+                "released" => state.released_keys = keys(value)?,
+                "mouse" => state.mouse = buttons(&value.split('/').collect::<Vec<_>>())?,
+                "cursor" => {
+                    let (x, y) = value.split_once(',').ok_or_else(|| format!("expected <x>,<y>, got \"{}\"", value))?;
+                    state.cursor_pos = Some(glm::Vec2::new(number(x)?, number(y)?));
+                },
+                _ => {
+                    let pad: usize = name.strip_prefix("pad")
+                        .and_then(|pad| pad.parse().ok())
+                        .ok_or_else(|| format!("unknown field \"{}\"", name))?;
+                    let parts: Vec<_> = value.split('/').collect();
+                    let [connected, down, pressed, released, current, previous] = parts[..] else {
+                        return Err(format!("expected 6 parts for {}, got \"{}\"", name, value));
+                    };
+                    if state.gamepads.len() <= pad {
+                        state.gamepads.resize(pad + 1, GamepadState::default());
+                    }
+                    state.gamepads[pad] = GamepadState {
+                        connected: connected == "1",
+                        buttons: buttons(&[down, pressed, released])?,
+                        axes: axes(current)?,
+                        prev_axes: axes(previous)?,
+                    };
+                },
+            }
+        }
+        Ok(state)
+    }
 }
 
+
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use winit::keyboard::KeyCode;
+
+    const SPACE: PhysicalKey = PhysicalKey::Code(KeyCode::Space);
+
+    #[test]
+    fn tap_within_a_frame_is_not_lost() {
+        let mut input = InputController::new();
+        input.handle_key(SPACE, Some(KeyKind::Space), ElementState::Pressed);
+        input.handle_key(SPACE, Some(KeyKind::Space), ElementState::Released);
+        let frame = input.next_frame(0.016);
+        assert!(!frame.is_key_pressed(KeyKind::Space));
+        assert!(frame.just_pressed(KeyKind::Space));
+        assert!(frame.just_released(KeyKind::Space));
+        assert_eq!(2, frame.events().len());
+
+        let frame = input.next_frame(0.016);
+        assert!(!frame.just_pressed(KeyKind::Space));
+        assert!(frame.events().is_empty());
+    }
+
+    #[test]
+    fn held_key_is_only_pressed_once() {
+        let enter = PhysicalKey::Code(KeyCode::Enter);
+        let mut input = InputController::new();
+        input.handle_key(enter, Some(KeyKind::Enter), ElementState::Pressed);
+        let frame = input.next_frame(0.5);
+        assert!(frame.just_pressed(KeyKind::Enter));
+        assert_eq!(0.0, frame.held_duration(KeyKind::Enter));
+
+        input.handle_key(enter, Some(KeyKind::Enter), ElementState::Pressed);
+        let frame = input.next_frame(0.5);
+        assert!(frame.is_key_pressed(KeyKind::Enter));
+        assert!(!frame.just_pressed(KeyKind::Enter));
+        let frame = input.next_frame(0.5);
+        assert_eq!(1.0, frame.held_duration(KeyKind::Enter));
+        assert_eq!(1.0, frame.held_duration(KeyBinding::Physical(KeyKind::Enter)));
+
+        input.handle_key(enter, Some(KeyKind::Enter), ElementState::Released);
+        let frame = input.next_frame(0.5);
+        assert!(frame.just_released(KeyKind::Enter));
+        assert_eq!(0.0, frame.held_duration(KeyKind::Enter));
+    }
+
+    #[test]
+    fn physical_bindings_ignore_layout() {
+        // The key above A on AZERTY types "z" but sits where W does on QWERTY.
+        let key = PhysicalKey::Code(KeyCode::KeyW);
+        let mut input = InputController::new();
+        input.handle_key(key, Some(KeyKind::Z), ElementState::Pressed);
+        let frame = input.next_frame(0.016);
+        assert!(frame.is_key_pressed(KeyKind::Z));
+        assert!(!frame.is_key_pressed(KeyKind::W));
+        assert!(frame.is_key_pressed(KeyBinding::Physical(KeyKind::W)));
+    }
+
+    #[test]
+    fn release_matches_what_was_typed() {
+        let one = PhysicalKey::Code(KeyCode::Digit1);
+        let mut input = InputController::new();
+        input.handle_key(one, Some(KeyKind::Digit1), ElementState::Pressed);
+        // Shift went down in between, so the release comes through as "!".
+        input.handle_key(one, None, ElementState::Released);
+        let frame = input.next_frame(0.016);
+        assert!(!frame.is_key_pressed(KeyKind::Digit1));
+        assert!(frame.just_released(KeyKind::Digit1));
+    }
+
+    #[test]
+    fn state_round_trips_through_text() {
+        let mut input = InputController::new();
+        assert_eq!("", input.state().to_string());
+        input.handle_key(SPACE, Some(KeyKind::Space), ElementState::Pressed);
+        input.handle_mouse_button(WinitMouseButton::Right, ElementState::Pressed);
+        input.handle_cursor(12.5, 300.25);
+        input.handle_gamepad(GamepadEvent::Axis { pad: 1, axis: GamepadAxis::LeftTrigger, value: 0.1 + 0.2 });
+        let state = input.next_frame(0.016);
+        assert_eq!(Ok(state.snapshot()), InputState::parse(&state.to_string()));
+        assert!(InputState::parse("pad0=1/0/0").is_err());
+    }
+
+    #[test]
+    fn gamepads_come_through_the_backend() {
+        use crate::core::gamepad::{MockGamepadBackend, RawGamepadEvent};
+
+        let backend = MockGamepadBackend::new();
+        let mut input = InputController::new();
+        input.set_gamepad_backend(Box::new(backend.clone()));
+        backend.push(RawGamepadEvent::Connected(1));
+        backend.push(RawGamepadEvent::Button { pad: 1, index: 0, pressed: true });
+        backend.push(RawGamepadEvent::Axis { pad: 1, index: 1, value: -1.0 });
+        let frame = input.next_frame(0.016);
+        assert!(frame.gamepad(0).is_some_and(|pad| !pad.is_connected()));
+        let pad = frame.gamepad(1).unwrap();
+        assert!(pad.is_connected());
+        assert!(pad.just_pressed(GamepadButton::South));
+        assert_eq!(1.0, pad.axis(GamepadAxis::LeftStickY));
+
+        backend.push(RawGamepadEvent::Disconnected(1));
+        let pad = *input.next_frame(0.016).gamepad(1).unwrap();
+        assert!(!pad.is_down(GamepadButton::South));
+        assert!(pad.just_released(GamepadButton::South));
+        assert_eq!(0.0, pad.axis(GamepadAxis::LeftStickY));
+    }
+}
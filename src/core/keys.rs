@@ -0,0 +1,319 @@
+// Every key the input layer knows about, and a bitset to keep track of which are down.
+//
+// Letters, digits and punctuation come from the text a key types, lowercased, so "K" and "k"
+// are the same key. Everything else mirrors winit's `NamedKey`. Physical keys (where a key sits
+// on the keyboard, whatever the layout) are named after the key in that spot on a US layout.
+use winit::keyboard::{Key, KeyCode, NamedKey, PhysicalKey};
+
+macro_rules! key_kinds {
+    (
+        characters { $($ch_key:ident = $ch:literal),* $(,)? }
+        named { $($named:ident),* $(,)? }
+        physical { $($code:ident = $code_key:ident),* $(,)? }
+    ) => {
+        #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+        pub enum KeyKind {
+            $($ch_key,)*
+            $($named,)*
+        }
+
+        impl KeyKind {
+            pub const ALL: &'static [KeyKind] = &[$(KeyKind::$ch_key,)* $(KeyKind::$named,)*];
+
+            pub fn name(self) -> &'static str {
+                match self {
+                    $(KeyKind::$ch_key => stringify!($ch_key),)*
+                    $(KeyKind::$named => stringify!($named),)*
+                }
+            }
+
+            fn from_char(ch: char) -> Option<KeyKind> {
+                match ch {
+                    $($ch => Some(KeyKind::$ch_key),)*
+                    _ => None,
+                }
+            }
+
+            fn from_named(key: NamedKey) -> Option<KeyKind> {
+                match key {
+                    $(NamedKey::$named => Some(KeyKind::$named),)*
+                    _ => None,
+                }
+            }
+
+            fn from_code(code: KeyCode) -> Option<KeyKind> {
+                match code {
+                    $(KeyCode::$code => Some(KeyKind::$code_key),)*
+                    _ => None,
+                }
+            }
+        }
+    };
+}
+
+key_kinds! {
+    characters {
+        A = 'a', B = 'b', C = 'c', D = 'd', E = 'e', F = 'f', G = 'g', H = 'h', I = 'i', J = 'j',
+        K = 'k', L = 'l', M = 'm', N = 'n', O = 'o', P = 'p', Q = 'q', R = 'r', S = 's', T = 't',
+        U = 'u', V = 'v', W = 'w', X = 'x', Y = 'y', Z = 'z', Digit0 = '0', Digit1 = '1',
+        Digit2 = '2', Digit3 = '3', Digit4 = '4', Digit5 = '5', Digit6 = '6', Digit7 = '7',
+        Digit8 = '8', Digit9 = '9', Minus = '-', Equal = '=', Comma = ',', Period = '.',
+        Slash = '/', Semicolon = ';', Quote = '\'', BracketLeft = '[', BracketRight = ']',
+        Backslash = '\\', Backquote = '`',
+    }
+    named {
+        Alt, AltGraph, CapsLock, Control, Fn, FnLock, NumLock, ScrollLock, Shift, Symbol,
+        SymbolLock, Meta, Hyper, Super, Enter, Tab, Space, ArrowDown, ArrowLeft, ArrowRight,
+        ArrowUp, End, Home, PageDown, PageUp, Backspace, Clear, Copy, CrSel, Cut, Delete, EraseEof,
+        ExSel, Insert, Paste, Redo, Undo, Accept, Again, Attn, Cancel, ContextMenu, Escape, Execute,
+        Find, Help, Pause, Play, Props, Select, ZoomIn, ZoomOut, BrightnessDown, BrightnessUp,
+        Eject, LogOff, Power, PowerOff, PrintScreen, Hibernate, Standby, WakeUp, AllCandidates,
+        Alphanumeric, CodeInput, Compose, Convert, FinalMode, GroupFirst, GroupLast, GroupNext,
+        GroupPrevious, ModeChange, NextCandidate, NonConvert, PreviousCandidate, Process,
+        SingleCandidate, HangulMode, HanjaMode, JunjaMode, Eisu, Hankaku, Hiragana,
+        HiraganaKatakana, KanaMode, KanjiMode, Katakana, Romaji, Zenkaku, ZenkakuHankaku, Soft1,
+        Soft2, Soft3, Soft4, ChannelDown, ChannelUp, Close, MailForward, MailReply, MailSend,
+        MediaClose, MediaFastForward, MediaPause, MediaPlay, MediaPlayPause, MediaRecord,
+        MediaRewind, MediaStop, MediaTrackNext, MediaTrackPrevious, New, Open, Print, Save,
+        SpellCheck, Key11, Key12, AudioBalanceLeft, AudioBalanceRight, AudioBassBoostDown,
+        AudioBassBoostToggle, AudioBassBoostUp, AudioFaderFront, AudioFaderRear,
+        AudioSurroundModeNext, AudioTrebleDown, AudioTrebleUp, AudioVolumeDown, AudioVolumeUp,
+        AudioVolumeMute, MicrophoneToggle, MicrophoneVolumeDown, MicrophoneVolumeUp,
+        MicrophoneVolumeMute, SpeechCorrectionList, SpeechInputToggle, LaunchApplication1,
+        LaunchApplication2, LaunchCalendar, LaunchContacts, LaunchMail, LaunchMediaPlayer,
+        LaunchMusicPlayer, LaunchPhone, LaunchScreenSaver, LaunchSpreadsheet, LaunchWebBrowser,
+        LaunchWebCam, LaunchWordProcessor, BrowserBack, BrowserFavorites, BrowserForward,
+        BrowserHome, BrowserRefresh, BrowserSearch, BrowserStop, AppSwitch, Call, Camera,
+        CameraFocus, EndCall, GoBack, GoHome, HeadsetHook, LastNumberRedial, Notification,
+        MannerMode, VoiceDial, TV, TV3DMode, TVAntennaCable, TVAudioDescription,
+        TVAudioDescriptionMixDown, TVAudioDescriptionMixUp, TVContentsMenu, TVDataService, TVInput,
+        TVInputComponent1, TVInputComponent2, TVInputComposite1, TVInputComposite2, TVInputHDMI1,
+        TVInputHDMI2, TVInputHDMI3, TVInputHDMI4, TVInputVGA1, TVMediaContext, TVNetwork,
+        TVNumberEntry, TVPower, TVRadioService, TVSatellite, TVSatelliteBS, TVSatelliteCS,
+        TVSatelliteToggle, TVTerrestrialAnalog, TVTerrestrialDigital, TVTimer, AVRInput, AVRPower,
+        ColorF0Red, ColorF1Green, ColorF2Yellow, ColorF3Blue, ColorF4Grey, ColorF5Brown,
+        ClosedCaptionToggle, Dimmer, DisplaySwap, DVR, Exit, FavoriteClear0, FavoriteClear1,
+        FavoriteClear2, FavoriteClear3, FavoriteRecall0, FavoriteRecall1, FavoriteRecall2,
+        FavoriteRecall3, FavoriteStore0, FavoriteStore1, FavoriteStore2, FavoriteStore3, Guide,
+        GuideNextDay, GuidePreviousDay, Info, InstantReplay, Link, ListProgram, LiveContent, Lock,
+        MediaApps, MediaAudioTrack, MediaLast, MediaSkipBackward, MediaSkipForward,
+        MediaStepBackward, MediaStepForward, MediaTopMenu, NavigateIn, NavigateNext, NavigateOut,
+        NavigatePrevious, NextFavoriteChannel, NextUserProfile, OnDemand, Pairing, PinPDown,
+        PinPMove, PinPToggle, PinPUp, PlaySpeedDown, PlaySpeedReset, PlaySpeedUp, RandomToggle,
+        RcLowBattery, RecordSpeedNext, RfBypass, ScanChannelsToggle, ScreenModeNext, Settings,
+        SplitScreenToggle, STBInput, STBPower, Subtitle, Teletext, VideoModeNext, Wink, ZoomToggle,
+        F1, F2, F3, F4, F5, F6, F7, F8, F9, F10, F11, F12, F13, F14, F15, F16, F17, F18, F19, F20,
+        F21, F22, F23, F24, F25, F26, F27, F28, F29, F30, F31, F32, F33, F34, F35,
+    }
+    physical {
+        Backquote = Backquote, Backslash = Backslash, BracketLeft = BracketLeft,
+        BracketRight = BracketRight, Comma = Comma, Digit0 = Digit0, Digit1 = Digit1,
+        Digit2 = Digit2, Digit3 = Digit3, Digit4 = Digit4, Digit5 = Digit5, Digit6 = Digit6,
+        Digit7 = Digit7, Digit8 = Digit8, Digit9 = Digit9, Equal = Equal, KeyA = A, KeyB = B,
+        KeyC = C, KeyD = D, KeyE = E, KeyF = F, KeyG = G, KeyH = H, KeyI = I, KeyJ = J, KeyK = K,
+        KeyL = L, KeyM = M, KeyN = N, KeyO = O, KeyP = P, KeyQ = Q, KeyR = R, KeyS = S, KeyT = T,
+        KeyU = U, KeyV = V, KeyW = W, KeyX = X, KeyY = Y, KeyZ = Z, Minus = Minus, Period = Period,
+        Quote = Quote, Semicolon = Semicolon, Slash = Slash, AltLeft = Alt, AltRight = Alt,
+        Backspace = Backspace, CapsLock = CapsLock, ContextMenu = ContextMenu,
+        ControlLeft = Control, ControlRight = Control, Enter = Enter, SuperLeft = Super,
+        SuperRight = Super, ShiftLeft = Shift, ShiftRight = Shift, Space = Space, Tab = Tab,
+        Convert = Convert, KanaMode = KanaMode, NonConvert = NonConvert, Delete = Delete, End = End,
+        Help = Help, Home = Home, Insert = Insert, PageDown = PageDown, PageUp = PageUp,
+        ArrowDown = ArrowDown, ArrowLeft = ArrowLeft, ArrowRight = ArrowRight, ArrowUp = ArrowUp,
+        NumLock = NumLock, Escape = Escape, Fn = Fn, FnLock = FnLock, PrintScreen = PrintScreen,
+        ScrollLock = ScrollLock, Pause = Pause, BrowserBack = BrowserBack,
+        BrowserFavorites = BrowserFavorites, BrowserForward = BrowserForward,
+        BrowserHome = BrowserHome, BrowserRefresh = BrowserRefresh, BrowserSearch = BrowserSearch,
+        BrowserStop = BrowserStop, Eject = Eject, LaunchMail = LaunchMail,
+        MediaPlayPause = MediaPlayPause, MediaStop = MediaStop, MediaTrackNext = MediaTrackNext,
+        MediaTrackPrevious = MediaTrackPrevious, Power = Power, AudioVolumeDown = AudioVolumeDown,
+        AudioVolumeMute = AudioVolumeMute, AudioVolumeUp = AudioVolumeUp, WakeUp = WakeUp,
+        Meta = Meta, Hyper = Hyper, Again = Again, Copy = Copy, Cut = Cut, Find = Find, Open = Open,
+        Paste = Paste, Props = Props, Select = Select, Undo = Undo, Hiragana = Hiragana,
+        Katakana = Katakana, F1 = F1, F2 = F2, F3 = F3, F4 = F4, F5 = F5, F6 = F6, F7 = F7, F8 = F8,
+        F9 = F9, F10 = F10, F11 = F11, F12 = F12, F13 = F13, F14 = F14, F15 = F15, F16 = F16,
+        F17 = F17, F18 = F18, F19 = F19, F20 = F20, F21 = F21, F22 = F22, F23 = F23, F24 = F24,
+        F25 = F25, F26 = F26, F27 = F27, F28 = F28, F29 = F29, F30 = F30, F31 = F31, F32 = F32,
+        F33 = F33, F34 = F34, F35 = F35,
+    }
+}
+
+impl KeyKind {
+    /// How many keys there are.
+    pub const COUNT: usize = KeyKind::ALL.len();
+
+    /// The key that typed `key`, ignoring case. `None` for keys we don't track, like dead keys
+    /// or characters outside the US layout.
+    pub fn from_logical(key: &Key) -> Option<KeyKind> {
+        match key {
+            Key::Named(named) => KeyKind::from_named(*named),
+            Key::Character(text) => {
+                let mut chars = text.chars().flat_map(char::to_lowercase);
+                match (chars.next(), chars.next()) {
+                    (Some(ch), None) => KeyKind::from_char(ch),
+                    _ => None,
+                }
+            },
+            _ => None,
+        }
+    }
+
+    /// The key in this spot on a US layout. Left and right modifiers are the same key.
+    pub fn from_physical(key: PhysicalKey) -> Option<KeyKind> {
+        match key {
+            PhysicalKey::Code(code) => KeyKind::from_code(code),
+            PhysicalKey::Unidentified(_) => None,
+        }
+    }
+
+    /// The key called `name`, ignoring case.
+    pub fn from_name(name: &str) -> Option<KeyKind> {
+        KeyKind::ALL.iter().copied().find(|key| key.name().eq_ignore_ascii_case(name))
+    }
+
+    pub fn index(self) -> usize {
+        self as usize
+    }
+}
+
+/// A key as a binding sees it: by what it types or by where it is.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum KeyBinding {
+    Logical(KeyKind),  // whichever key types this; `Q` is the key labelled Q
+    Physical(KeyKind), // the key in this spot on a US layout; `Q` is the top left letter, A on AZERTY
+}
+
+impl KeyBinding {
+    fn index(self) -> usize {
+        match self {
+            KeyBinding::Logical(key) => key.index(),
+            KeyBinding::Physical(key) => KeyKind::COUNT + key.index(),
+        }
+    }
+
+    fn from_index(index: usize) -> KeyBinding {
+        if index < KeyKind::COUNT {
+            KeyBinding::Logical(KeyKind::ALL[index])
+        } else {
+            KeyBinding::Physical(KeyKind::ALL[index - KeyKind::COUNT])
+        }
+    }
+}
+
+impl KeyBinding {
+    /// Reads `key:<Key>` or `physical:<Key>`, the way bindings are written in config files.
+    pub fn parse(text: &str) -> Result<KeyBinding, String> {
+        let (kind, name) = text.trim().split_once(':')
+            .ok_or_else(|| format!("expected key:<Key> or physical:<Key>, got \"{}\"", text.trim()))?;
+        let key = KeyKind::from_name(name.trim()).ok_or_else(|| format!("unknown key \"{}\"", name.trim()))?;
+        match kind.trim().to_ascii_lowercase().as_str() {
+            "key" => Ok(KeyBinding::Logical(key)),
+            "physical" => Ok(KeyBinding::Physical(key)),
+            kind => Err(format!("unknown key kind \"{}\"", kind)),
+        }
+    }
+}
+
+impl std::fmt::Display for KeyBinding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            KeyBinding::Logical(key) => write!(f, "key:{}", key.name()),
+            KeyBinding::Physical(key) => write!(f, "physical:{}", key.name()),
+        }
+    }
+}
+
+impl From<KeyKind> for KeyBinding {
+    fn from(key: KeyKind) -> Self {
+        KeyBinding::Logical(key)
+    }
+}
+
+const KEY_SET_WORDS: usize = (2 * KeyKind::COUNT).div_ceil(64);
+
+/// A set of keys, logical and physical, one bit each.
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+pub struct KeySet {
+    words: [u64; KEY_SET_WORDS],
+}
+
+impl KeySet {
+    pub fn new() -> Self {
+        KeySet::default()
+    }
+
+    pub fn insert(&mut self, key: impl Into<KeyBinding>) {
+        let index = key.into().index();
+        self.words[index / 64] |= 1 << (index % 64);
+    }
+
+    pub fn remove(&mut self, key: impl Into<KeyBinding>) {
+        let index = key.into().index();
+        self.words[index / 64] &= !(1 << (index % 64));
+    }
+
+    pub fn contains(&self, key: impl Into<KeyBinding>) -> bool {
+        let index = key.into().index();
+        self.words[index / 64] & (1 << (index % 64)) != 0
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.iter().all(|word| *word == 0)
+    }
+
+    pub fn clear(&mut self) {
+        self.words = [0; KEY_SET_WORDS];
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = KeyBinding> + '_ {
+        self.words.iter().enumerate().flat_map(|(i, word)| {
+            (0..64)
+                .filter(move |bit| word & (1 << bit) != 0)
+                .map(move |bit| KeyBinding::from_index(i * 64 + bit))
+        })
+    }
+}
+
+impl std::fmt::Debug for KeySet {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn logical_keys_ignore_case() {
+        let upper = Key::Character("K".into());
+        let lower = Key::Character("k".into());
+        assert_eq!(Some(KeyKind::K), KeyKind::from_logical(&upper));
+        assert_eq!(Some(KeyKind::K), KeyKind::from_logical(&lower));
+        assert_eq!(Some(KeyKind::Digit7), KeyKind::from_logical(&Key::Character("7".into())));
+        assert_eq!(Some(KeyKind::Escape), KeyKind::from_logical(&Key::Named(NamedKey::Escape)));
+        assert_eq!(None, KeyKind::from_logical(&Key::Character("ß".into())));
+    }
+
+    #[test]
+    fn physical_keys_use_us_names() {
+        assert_eq!(Some(KeyKind::W), KeyKind::from_physical(PhysicalKey::Code(KeyCode::KeyW)));
+        assert_eq!(Some(KeyKind::Shift), KeyKind::from_physical(PhysicalKey::Code(KeyCode::ShiftRight)));
+        assert_eq!(Some(KeyKind::F12), KeyKind::from_physical(PhysicalKey::Code(KeyCode::F12)));
+    }
+
+    #[test]
+    fn key_set_holds_every_key() {
+        let mut set = KeySet::new();
+        let last = *KeyKind::ALL.last().unwrap();
+        set.insert(KeyBinding::Physical(last));
+        set.insert(KeyKind::A);
+        assert!(set.contains(KeyBinding::Physical(last)));
+        assert!(!set.contains(last));
+        assert_eq!(vec![KeyBinding::Logical(KeyKind::A), KeyBinding::Physical(last)], set.iter().collect::<Vec<_>>());
+
+        set.remove(KeyKind::A);
+        set.remove(KeyBinding::Physical(last));
+        assert!(set.is_empty());
+    }
+}
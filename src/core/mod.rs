@@ -1,4 +1,35 @@
+// A fieldless enum that knows its variants and their names, for anything that has to be written
+// to or read from a config file.
+macro_rules! named_enum {
+    ($(#[$meta:meta])* $vis:vis enum $name:ident { $($variant:ident),* $(,)? }) => {
+        $(#[$meta])*
+        $vis enum $name {
+            $($variant,)*
+        }
+
+        impl $name {
+            pub const ALL: &'static [$name] = &[$($name::$variant,)*];
+
+            pub fn name(self) -> &'static str {
+                match self {
+                    $($name::$variant => stringify!($variant),)*
+                }
+            }
+
+            /// The variant called `name`, ignoring case.
+            pub fn from_name(name: &str) -> Option<$name> {
+                $name::ALL.iter().copied().find(|variant| variant.name().eq_ignore_ascii_case(name))
+            }
+        }
+    };
+}
+
+pub mod action;
+pub mod gamepad;
 pub mod input;
+pub mod keys;
+pub mod rng;
+pub mod timestep;
 
 
 
@@ -0,0 +1,95 @@
+// Accumulator driven fixed timestep. The frame loop feeds in however much wall time has passed
+// and gets back how many simulation ticks to run, so physics runs at the same rate no matter how
+// fast (or slow) we are rendering.
+pub struct FixedTimestep {
+    tick_rate: u32,
+    max_steps: u32, // cap on catch-up ticks per frame so a long stall can't snowball.
+    accumulator: f32,
+}
+
+
+
+impl FixedTimestep {
+    pub fn new(tick_rate: u32, max_steps: u32) -> Self {
+        assert!(tick_rate > 0, "tick rate must be positive");
+        assert!(max_steps > 0, "need at least one step per frame");
+        FixedTimestep {
+            tick_rate,
+            max_steps,
+            accumulator: 0.0f32,
+        }
+    }
+
+    pub fn tick_rate(&self) -> u32 {
+        self.tick_rate
+    }
+
+    pub fn set_tick_rate(&mut self, tick_rate: u32) {
+        assert!(tick_rate > 0, "tick rate must be positive");
+        self.tick_rate = tick_rate;
+        self.accumulator = self.accumulator.min(self.dt());
+    }
+
+    pub fn max_steps(&self) -> u32 {
+        self.max_steps
+    }
+
+    /// Length of one simulation tick in seconds.
+    pub fn dt(&self) -> f32 {
+        1.0f32 / self.tick_rate as f32
+    }
+
+    /// Adds `frame_delta` seconds to the accumulator and returns the number of ticks to run this
+    /// frame. Time beyond `max_steps` ticks is thrown away rather than carried into the next frame.
+    pub fn advance(&mut self, frame_delta: f32) -> u32 {
+        let dt = self.dt();
+        self.accumulator += frame_delta.max(0.0f32);
+
+        let mut steps = 0;
+        while self.accumulator >= dt && steps < self.max_steps {
+            self.accumulator -= dt;
+            steps += 1;
+        }
+
+        if steps == self.max_steps && self.accumulator >= dt {
+            self.accumulator %= dt;
+        }
+        steps
+    }
+
+    /// How far we are between the last tick and the next one, in `[0, 1)`. The renderer blends
+    /// the previous and current positions with this.
+    pub fn alpha(&self) -> f32 {
+        (self.accumulator / self.dt()).clamp(0.0f32, 1.0f32)
+    }
+
+    pub fn reset(&mut self) {
+        self.accumulator = 0.0f32;
+    }
+}
+
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn runs_whole_ticks_and_keeps_remainder() {
+        let mut timestep = FixedTimestep::new(100, 10);
+        assert_eq!(0, timestep.advance(0.005));
+        assert!((timestep.alpha() - 0.5).abs() < 1e-4);
+        assert_eq!(1, timestep.advance(0.010));
+        assert!((timestep.alpha() - 0.5).abs() < 1e-4);
+        assert_eq!(3, timestep.advance(0.025));
+        assert!(timestep.alpha() < 1e-3);
+    }
+
+    #[test]
+    fn caps_catch_up_steps() {
+        let mut timestep = FixedTimestep::new(60, 4);
+        assert_eq!(4, timestep.advance(1.0));
+        assert!(timestep.alpha() < 1.0);
+        assert_eq!(0, timestep.advance(0.0));
+    }
+}
@@ -1,4 +1,5 @@
 use crate::renderer::texture::GLTexture;
+use std::rc::Rc;
 use crate::renderer::*;
 use crate::core::input::{InputController,InputState};
 use crate::core::FrameCounter;
@@ -15,6 +16,7 @@ pub struct Example2D {
     camera: camera::OrthographicCameraController,
     input: InputController,
     frame_counter: FrameCounter,
+    checkerboard: Option<Rc<GLTexture>>,
 }
 
 
@@ -29,12 +31,15 @@ impl Example2D {
             gl.viewport(0, 0, width, height);
             let renderer = Renderer2D::new(gl, width, height);
             let camera = camera::OrthographicCameraController::new(width as f32 / height as f32, false);
-            let checkerboard = GLTexture::new("resources/Checkerboard.png");
+            let checkerboard = renderer.load_texture("resources/Checkerboard.png")
+                .map_err(|e| eprintln!("Couldn't load the checkerboard: {}", e))
+                .ok();
             Self {
                 renderer,
                 camera, 
                 input: InputController::new(),
                 frame_counter: FrameCounter::new(),
+                checkerboard,
             }
         }
     }
@@ -46,12 +51,19 @@ impl Example2D {
     pub fn update(&mut self) {
         // Always handle frame counter and input at the beginning of update.
         let delta = self.update_frames();
-        let input_state = self.input.state();
+        let input_state = self.input.next_frame(delta);
         //****************************************
 
         self.camera.update(delta, &input_state);
         self.renderer.begin_scene(self.camera.get_camera());
 
+        // Drawn first, there's no depth test.
+        if let Some(checkerboard) = &self.checkerboard {
+            let transform = glm::translate(&glm::Mat4::identity(), &glm::Vec3::new(0.0, 0.0, -0.1));
+            let transform = glm::scale(&transform, &glm::Vec3::new(10.0, 10.0, 1.0));
+            self.renderer.draw_quad_texture(&transform, checkerboard, 10.0, &glm::Vec4::new(1.0, 0.9, 0.9, 1.0));
+        }
+
         let mut pos = glm::Vec3::new(-0.5, -0.5, 0.5);
         let size = glm::Vec2::new(0.8, 0.4);
         let color = glm::Vec4::new(0.8, 0.2, 0.2, 1.0);
@@ -1,972 +1,702 @@
 // pub mod renderer;
 use crate::renderer::*;
-use crate::physics::*;
+use crate::sim::*;
 use crate::core::FrameCounter;
-use crate::core::input::{InputController, InputState, KeyKind, KeyMap};
+use crate::core::timestep::FixedTimestep;
+use crate::core::action::{Action, Binding, ConfigError, InputConfig};
+use crate::core::input::{InputController, InputState};
+use crate::controls::SharedInput;
+use crate::menu::{Menu, MenuEvent, MenuInput, MenuView, PauseEvent, PauseMenu};
+use crate::replay::{self, MatchCommand, Recorder, Replay, ReplayPlayer};
 
-use std::collections::HashMap;
-use std::time::{Instant, Duration};
-use std::ops::Deref;
-use std::cell::RefCell;
+use std::time::{SystemTime, UNIX_EPOCH};
 
-use winit::event::KeyEvent;
-use glow::*;
+use winit::event::{ElementState, KeyEvent, MouseButton};
+use glow::Context;
 use glutin::prelude::GlDisplay;
 use nalgebra_glm as glm;
 
+/// Simulation ticks per second.
+pub const TICK_RATE: u32 = 120;
+/// Most ticks we will run to catch up after a slow frame.
+pub const MAX_TICKS_PER_FRAME: u32 = 8;
+/// Where the bindings are kept, see `core::action` for the format.
+pub const INPUT_CONFIG_PATH: &str = "input.cfg";
+/// Where the last match played is saved, see `replay` for the format.
+pub const REPLAY_PATH: &str = "last.replay";
+/// Playback speeds Confirm steps through while watching a replay.
+pub const REPLAY_SPEEDS: [f32; 5] = [0.25, 0.5, 1.0, 2.0, 4.0];
+/// How far a seek jumps while watching a replay, in seconds.
+pub const REPLAY_SEEK: f32 = 5.0;
+/// How dark the court goes behind the pause menu, from 0 to 1.
+pub const PAUSE_DIM: f32 = 0.6;
+
+// Joins the headless `GameData` simulation to a renderer and an input source.
 pub struct Game {
-    renderer: Renderer,
+    renderer: CourtRenderer,
     input: InputController,
+    shared_input: SharedInput, // what the human controllers read each tick
+    mouse_control: bool,       // whether the first human paddle follows the mouse
+    recorder: Option<Recorder>, // recording the match being played
+    replay: Option<ReplayPlayer>, // the replay being watched
+    controls: InputConfig,
+    rebinding: Option<(Option<usize>, Action)>, // waiting for a new binding for this action
     game_data: GameData,
     scene_state: SceneState,
-    menu_state: MenuState,
-    players: u8,
+    menu: Menu,
+    pause_menu: PauseMenu,
+    shown_menu: Option<String>, // menu page last written to the console
     frame_counter: FrameCounter,
+    timestep: FixedTimestep,
 }
 
 impl Game {
     pub fn new<D: GlDisplay>(gl_display: &D, width: i32, height: i32) -> Self {
-        let players = 0;
-        let game_data = GameData::new(players);
-        let renderer = Renderer::new(gl_display, width, height, &game_data);
+        let mut input = InputController::new();
+        #[cfg(target_os = "linux")]
+        input.set_gamepad_backend(Box::new(crate::core::gamepad::JoystickBackend::open()));
+        // The computer plays itself behind the menu.
+        let game_data = GameData::new(MatchConfig::new(0, MatchRules::default()));
+        let renderer = CourtRenderer::new(gl_display, width, height);
+        let replay = replay_from_args();
 
         Game {
             renderer,
-            input: InputController::new(),
-            players: 1, // Update this with number of players
-            game_data: game_data,
-            scene_state: SceneState::Playing, // TODO: FIX THIS TO DEFAULT TO MENU
-            menu_state: MenuState::PlayerSelect,
+            input,
+            shared_input: SharedInput::new(),
+            mouse_control: false,
+            recorder: None,
+            scene_state: if replay.is_some() { SceneState::Replay } else { SceneState::Menu },
+            replay,
+            controls: load_controls(),
+            rebinding: None,
+            game_data,
+            menu: Menu::new(),
+            pause_menu: PauseMenu::new(),
+            shown_menu: None,
             frame_counter: FrameCounter::new(),
+            timestep: FixedTimestep::new(TICK_RATE, MAX_TICKS_PER_FRAME),
         }
     }
 
+    pub fn set_tick_rate(&mut self, tick_rate: u32) {
+        self.timestep.set_tick_rate(tick_rate);
+    }
+
     pub fn resize(&mut self, width: i32, height: i32) {
         self.renderer.resize(width, height);
     }
 
     pub fn update_cursor(&mut self, x: f64, y: f64) {
-        let n_x = (x / self.renderer.width as f64) * 2.0f64 - 1.0f64;
-        let n_y = (y / self.renderer.height as f64) * 2.0f64 - 1.0f64;
-        self.input.handle_cursor(n_x as f32, n_y as f32);
+        self.input.handle_cursor(x as f32, y as f32);
     }
 
     pub fn handle_keyboard(&mut self, event: KeyEvent) {
         self.input.handle_keyboard(event)
     }
 
-    pub fn update(&mut self) {
-        // Update frame every loop no matter what.
-        let delta = self.update_frames();
-
-        match self.scene_state {
-            SceneState::Menu => {
-                match self.menu_state {
-                    MenuState::PlayerSelect =>  { },
-                }
-            },
-            SceneState::Playing => {
-                let input_state = self.input.state();
-                self.game_data.update(delta, input_state);
-                self.renderer.draw(&self.game_data);
-            },
-        };
+    pub fn handle_mouse_button(&mut self, button: MouseButton, state: ElementState) {
+        self.input.handle_mouse_button(button, state)
     }
 
-    fn update_frames(&mut self) -> f32 {
-        let delta = self.frame_counter.increment();
-        match self.frame_counter.fps() {
-            Some(fps) => println!("{:.2} fps", fps),
-            None => { }
+    /// Pauses the match when the window loses focus, so nobody loses a point while away.
+    pub fn handle_focus(&mut self, focused: bool) {
+        if !focused && matches!(self.scene_state, SceneState::Playing)
+            && matches!(self.game_data.state(), GameState::Playing | GameState::Resuming) {
+            self.pause();
         }
-        delta
     }
-}
- 
-enum SceneState {
-    Menu,           // Will handle menu navigation
-    Playing,        // Regular gameplay
-}
-
-enum MenuState {
-    PlayerSelect,
-}
-
-enum GameState {
-    Starting,
-    Playing,
-    Pause,
-    WinScreen,
-}
 
-struct GameData {
-    start_time: Instant,
-    state: GameState,
-    ball: Ball,
-    players: Vec<Player>,
-    surfaces: Vec<Surface>,
-}
-
-impl GameData {
-    fn new(players: u32) -> Self {
-        let mut next_item_id = 0; // this is so stupid lol
-                                  //
-        // Paddles.
-        let x_pos = 0.99;
-        let left_paddle = Paddle::new(
-            next_item_id, 
-            glm::Vec2::new(-x_pos + (PADDLE_WIDTH / 2.0f32), 0.0), 
-            glm::Vec2::new(0.0, 1.0),
-            PADDLE_WIDTH, 
-            PADDLE_HEIGHT, 
-            false,
-        );
-        next_item_id += 1;
-        let right_paddle = Paddle::new(
-            next_item_id, 
-            glm::Vec2::new(x_pos - (PADDLE_WIDTH / 2.0f32), 0.0), 
-            glm::Vec2::new(0.0, 1.0),
-            PADDLE_WIDTH, 
-            PADDLE_HEIGHT, 
-            true,
-        );
-        next_item_id += 1;
-
-        // Balls.
-        let ball = Ball::new(next_item_id, 0.02);
-
-        // Extra surfaces
-        let floor = Surface { 
-            a: glm::Vec2::new(-1.0, -1.0),
-            b: glm::Vec2::new(1.0, -1.0),
-        };
-        let ceiling = Surface {
-            a: glm::Vec2::new(1.0, 1.0),
-            b: glm::Vec2::new(-1.0, 1.0),
-        };
-
-
-        let left_keymap = match players {
-            1 => {
-                Some(KeyMap {
-                    move_down: vec![KeyKind::ArrowDown, KeyKind::A, KeyKind::J],
-                    move_up: vec![KeyKind::ArrowUp, KeyKind::Q, KeyKind::K],
-                })
-            },
-            2 => { 
-                Some(KeyMap {
-                    move_down: vec![KeyKind::A, KeyKind::J],
-                    move_up: vec![KeyKind::Q, KeyKind::K],
-                })
-            },
-            _ => None
-        };
-
-        let right_keymap = match players {
-            2 => {
-                Some(KeyMap {
-                    move_down: vec![KeyKind::ArrowDown],
-                    move_up: vec![KeyKind::ArrowUp],
-                })
-            }
-            _ => None,
-        };
+    pub fn update(&mut self) {
+        // Update frame every loop no matter what.
+        let delta = self.update_frames();
 
-        let player1 = Player::new(0, left_paddle, left_keymap);
-        let player2 = Player::new(1, right_paddle, right_keymap);
+        let input_state = self.input.next_frame(delta);
+        let cursor_y = input_state.cursor().map(|cursor| self.renderer.cursor_to_court(&cursor).y);
+        self.shared_input.set_state(input_state.clone());
+        self.shared_input.set_cursor_y(cursor_y);
 
-        GameData {
-            start_time: Instant::now(),
-            state: GameState::Starting, // TODO: fix this?
-            ball,
-            players: vec![player1, player2],
-            surfaces: vec![floor, ceiling],
+        if let SceneState::Replay = self.scene_state {
+            self.update_replay(delta, &input_state);
+            return;
         }
-    }
-
-    fn reset(&mut self) {
-        self.state = GameState::Starting;
-
-        self.ball.reset();
-        self.start_time = Instant::now();
-    }
-
-    fn ball(&self) -> &Ball {
-        &self.ball
-    }
-
-    fn players(&self) -> &[Player] {
-        &self.players
-    }
 
-    fn pause(&mut self) {
-        match self.state {
-            GameState::Playing => 
-                self.state = GameState::Pause,
-            _ => { },
+        if let Some(recorder) = &mut self.recorder {
+            recorder.input(&input_state, cursor_y);
         }
-    }
-
-    fn unpause(&mut self) {
-        match self.state {
-            GameState::Pause => 
-                self.state = GameState::Playing, 
-            _ => {},
+        let steps = self.timestep.advance(delta);
+        for _ in 0..steps {
+            self.game_data.update(self.timestep.dt());
+            if let Some(recorder) = &mut self.recorder {
+                recorder.tick();
+            }
+            for event in self.game_data.drain_events() {
+                self.handle_event(event);
+            }
         }
-    }
-
-    fn update(&mut self, delta: f32, input: InputState) {
-        let time = self.start_time.elapsed().as_secs_f32();
-        match &self.state {
-            GameState::Starting => {
-                if time >= 1.0f32 {
-                    self.state = GameState::Playing;
-                }
-            },
-            GameState::Playing => {
-                self.ball.apply_velocity(delta);
-
-                for player in &self.players {
-                    let surface = player.paddle.surface(self.ball.position);
-                    resolve_collision(&mut self.ball, &surface, 1.5f32);
-                }
 
-                for surface in &self.surfaces {
-                    resolve_collision(&mut self.ball, &surface, 0.0f32);
-                }
-
-                self.ball.clamp_velocity();
-
-                if self.ball.position.x > 1.0f32 {
-                    // SCORE FOR LEFT PADDLE
-                    self.reset();
-                    return;
-                } else if self.ball.position.x < -1.0f32 {
-                    // SCORE FOR RIGHT PADDLE
-                    self.reset();
-                    return;
-                }
-
-                for player in &mut self.players {
-                    player.update(delta, &input, &self.ball);
+        match self.scene_state {
+            SceneState::Menu => {
+                // Keep the demo match going for as long as the menu is up.
+                if self.game_data.state() == GameState::WinScreen {
+                    self.game_data.restart();
                 }
 
-                if input.is_key_pressed(&KeyKind::Space) {
-                    self.pause();
-                    return;
+                if let Some((player, action)) = self.rebinding {
+                    self.finish_rebind(&input_state, player, action);
+                } else {
+                    match menu_input(&input_state, &self.controls).and_then(|input| self.menu.handle(input)) {
+                        Some(MenuEvent::Start(config)) => self.start_match(config),
+                        Some(MenuEvent::Rebind { player, action }) => {
+                            println!("Press the new binding for {}", action.name());
+                            self.rebinding = Some((player, action));
+                        },
+                        None => self.report_menu(),
+                    }
                 }
             },
-            GameState::Pause => 
-            { 
-                if input.is_key_pressed(&KeyKind::Enter) {
-                    self.unpause();
+            SceneState::Playing => {
+                let global = &self.controls.global;
+                if let Some((player, action)) = self.rebinding {
+                    self.finish_rebind(&input_state, player, action);
+                } else if self.game_data.state() == GameState::Pause {
+                    self.update_pause_menu(&input_state);
+                } else if global.just_pressed(Action::Pause, &input_state) {
+                    if let GameState::Playing | GameState::Resuming = self.game_data.state() {
+                        self.pause();
+                    }
+                } else if global.just_pressed(Action::Back, &input_state)
+                    && self.game_data.state() == GameState::WinScreen {
+                    self.open_menu();
+                } else if global.just_pressed(Action::Confirm, &input_state)
+                    && self.game_data.state() == GameState::WinScreen {
+                    self.command(MatchCommand::Restart);
+                } else if let Some(server) = self.human_serving(&input_state) {
+                    self.command(MatchCommand::Serve(server));
+                } else if input_state.mouse().just_pressed(crate::core::input::MouseButton::Left as usize)
+                    && self.game_data.config().players > 0 {
+                    self.command(MatchCommand::Mouse(!self.mouse_control));
                 }
             },
-            GameState::WinScreen => { },
+            SceneState::Replay => { },
         };
 
-    }
-}
-
-fn resolve_collision(ball: &mut Ball, surface: &Surface, factor: f32) {
-    if check_collision(ball, &surface) {
-        // TODO: resolve collision
-        // ball.velocity *= -1.0;
-        ball.velocity = calculate_bounce_velocity(surface, ball.velocity, factor);
-        // ball.velocity *= 0.0;
-        // println!("COLLISION DETECTED:\n{}\n\n", ball.velocity);
-    }
-}
-
-fn check_collision(ball: &Ball, surface: &Surface) -> bool {
-    // ceiling surface should be Vec2(1.0, -1.0) for surface normal pointing down.
-    // floor surface should be Vec2(-1.0, 1.0) for surface normal pointing up
-    let pos = ball.position;
-    let closest = surface.find_closest_point(&pos);
-    let distance = glm::distance(&closest, &pos);
-    // let distance = (((closest.x - pos.x).powi(2)) + ((closest.y - pos.x).powi(2))).sqrt();
-    // println!("{}, {} ({})", distance, ball.position, ball.radius);
-
-    // if distance < ball.radius * 10.0f32 {
-    //     println!("Approaching surface: {} ", distance);
-    // }
-
-    if distance <= ball.radius {
-        return true;
+        let menu = match self.scene_state {
+            SceneState::Menu => Some(self.menu.view()),
+            SceneState::Playing if self.game_data.state() == GameState::Pause => Some(self.pause_menu.view()),
+            _ => None,
+        };
+        self.renderer.draw(&self.game_data, self.timestep.alpha(), menu);
     }
 
-    false
-}
-fn calculate_bounce_velocity(surface: &Surface, velocity: glm::Vec2, factor: f32) -> glm::Vec2 {
-    let v_dir = surface.b - surface.a;
-    let mut normal = glm::Vec2::new(-v_dir.y, v_dir.x);
-    normal = normal.normalize();
-    
-    let dot = normal.dot(&velocity);
-    let vx = velocity.x - 2.0f32 * dot * normal.x;
-    let vy = velocity.y - 2.0f32 * dot * normal.y;
-
-    glm::Vec2::new(vx, vy)
-}
-
-pub struct Player {
-    id: u32,
-    score: u32,
-    paddle: Paddle,
-    keymap: Option<KeyMap>,
-}
-
-impl Player {
-    fn new(id: u32, paddle: Paddle, keymap: Option<KeyMap>) -> Self {
-        Player {
-            id,
-            score: 0u32,
-            paddle,
-            keymap,
+    // Does `command` to the match being played and records it.
+    fn command(&mut self, command: MatchCommand) {
+        if let MatchCommand::Mouse(on) = command {
+            self.mouse_control = on;
+            let controller = self.human_controller(0);
+            self.game_data.set_controller(0, controller);
+            println!("Player 1 plays with the {}", if on { "mouse" } else { "keyboard" });
+        }
+        replay::apply_command(&mut self.game_data, command);
+        if let Some(recorder) = &mut self.recorder {
+            recorder.command(command);
         }
     }
 
-    fn reset(&mut self) {
-        self.score = 0;
-    }
-
-    fn increment_score(&mut self) {
-        self.score += 1;
+    fn pause(&mut self) {
+        self.command(MatchCommand::Pause);
+        self.pause_menu.reset();
+        self.shown_menu = None;
     }
 
-    fn update(&mut self, delta: f32, input: &InputState, ball: &Ball) {
-        match &self.keymap {
-            Some(map) => { 
-                if input.any_pressed(&map.move_down) {
-                    self.paddle.move_down(delta);
-                } else if input.any_pressed(&map.move_up) {
-                    self.paddle.move_up(delta);
-                }
+    // The match is paused: the pause menu takes the input, and Pause itself resumes.
+    fn update_pause_menu(&mut self, input: &InputState) {
+        let event = if self.controls.global.just_pressed(Action::Pause, input) {
+            Some(PauseEvent::Resume)
+        } else {
+            menu_input(input, &self.controls).and_then(|input| self.pause_menu.handle(input))
+        };
+        match event {
+            Some(PauseEvent::Resume) => self.command(MatchCommand::Unpause),
+            Some(PauseEvent::Restart) => self.command(MatchCommand::Restart),
+            Some(PauseEvent::Quit) => self.open_menu(),
+            Some(PauseEvent::ToggleMouse) => self.command(MatchCommand::Mouse(!self.mouse_control)),
+            Some(PauseEvent::Rebind { player, action }) => {
+                println!("Press the new binding for {}", action.name());
+                self.rebinding = Some((player, action));
             },
-            None => {
-               /*
-                * This is a computer player.
-                *  - Move the paddle when the ball is moving towards my paddle
-                *  - Move the paddle in the y direction the ball is moving.
-                *
-                * */ 
-                let x_offset = 1.3f32; // TODO: 1.0 easy, 1.3, med, 1.6 hard
-                if self.paddle.position.x.signum() == ball.velocity.x.signum() && 
-                    (ball.position.x - self.paddle.position.x).abs() <= x_offset {
-                    let t = (self.paddle.position.x - ball.position.x) / ball.velocity.x;
-                    let target_y = ball.position.y + (t * ball.velocity.y);
-
-                    let y_offset = 0.05f32; // TODO: randomize this based on difficulty
-                    let pos = &ball.position;
-                    let y_diff = target_y - self.paddle.position.y;
-                    if y_diff < -y_offset {
-                        self.paddle.move_down(delta);
-                    } else if y_diff > y_offset {
-                        self.paddle.move_up(delta);
-                    }
-                }
-           },
+            None => { },
+        }
+        // The rows are drawn over the court; the console names the highlighted one until we can
+        // draw text.
+        if self.game_data.state() == GameState::Pause {
+            let page = self.pause_menu.describe(&self.controls, self.mouse_control);
+            self.show_menu(page);
         }
     }
-}
-
-pub struct Paddle {
-    id: u64,
-    width: f32,
-    height: f32,
-    position: glm::Vec2,
-    velocity: glm::Vec2,
-    vertices: [f32;30],
-}
-
-static X1_PADDLE: f32 = -0.015;
-static X2_PADDLE: f32 = 0.015;
-static Y1_PADDLE: f32 = -0.1;
-static Y2_PADDLE: f32 = 0.1;
-
-static PADDLE_WIDTH: f32 = X2_PADDLE - X1_PADDLE;
-static PADDLE_HEIGHT: f32 = Y2_PADDLE - Y1_PADDLE;
-
-static PADDLE_VERTICES: [f32;30] = [
-    X1_PADDLE, Y1_PADDLE,  1.0,  1.0,  1.0,
-    X2_PADDLE, Y2_PADDLE,  1.0,  1.0,  1.0,
-    X2_PADDLE, Y1_PADDLE,  1.0,  1.0,  1.0,
-
-    X1_PADDLE, Y1_PADDLE,  1.0,  1.0,  1.0,
-    X2_PADDLE, Y2_PADDLE,  1.0,  1.0,  1.0,
-    X1_PADDLE, Y2_PADDLE,  1.0,  1.0,  1.0,
-];
-
-static QUAD_VERTICES: [f32;30] = [
-    -1.0, -1.0,  1.0,  1.0,  1.0,
-     1.0,  1.0,  1.0,  1.0,  1.0,
-     1.0, -1.0,  1.0,  1.0,  1.0,
-
-    -1.0, -1.0,  1.0,  1.0,  1.0,
-     1.0,  1.0,  1.0,  1.0,  1.0,
-    -1.0,  1.0,  1.0,  1.0,  1.0,
-];
 
-impl Paddle {
-    fn new(id: u64, position: glm::Vec2, velocity: glm::Vec2, width: f32, height: f32, auto: bool) -> Self {
-
-        let mut paddle = Paddle {
-            id,
-            width,
-            height,
-            position,
-            velocity,
-            vertices: PADDLE_VERTICES.clone(), 
+    // Watching a replay: Pause pauses, Confirm changes speed, paddle moves seek and Back leaves.
+    fn update_replay(&mut self, delta: f32, input: &InputState) {
+        let Some(player) = &mut self.replay else {
+            return;
         };
-        paddle.clamp_position();
-        
-        return paddle
-    }
-
-    fn reset(&mut self) {
-        self.move_y(0.0f32);
-    }
+        let global = &self.controls.global;
+        let moves = self.controls.players[0].merged(&self.controls.players[1]);
+        let seek = (REPLAY_SEEK * player.replay().tick_rate as f32) as u64;
+        let mut changed = true;
+        if global.just_pressed(Action::Back, input) {
+            self.open_menu();
+            return;
+        } else if global.just_pressed(Action::Pause, input) {
+            player.set_paused(!player.is_paused());
+        } else if global.just_pressed(Action::Confirm, input) {
+            let next = REPLAY_SPEEDS.iter().position(|speed| *speed > player.speed()).unwrap_or(0);
+            player.set_speed(REPLAY_SPEEDS[next]);
+        } else if moves.just_pressed(Action::MoveUp, input) {
+            player.seek(player.position() + seek);
+        } else if moves.just_pressed(Action::MoveDown, input) {
+            player.seek(player.position().saturating_sub(seek));
+        } else {
+            changed = false;
+        }
 
-    fn id(&self) -> u64 {
-        self.id
+        let was_finished = player.is_finished();
+        player.update(delta);
+        if changed || player.is_finished() != was_finished {
+            let seconds = |ticks: u64| ticks as f32 / player.replay().tick_rate as f32;
+            println!("Replay {:.1}s / {:.1}s at {}x{}", seconds(player.position()), seconds(player.replay().length()),
+                player.speed(), if player.is_paused() { ", paused" } else { "" });
+        }
+        self.renderer.draw(player.game(), player.alpha(), None);
+    }
+
+    // Swaps the demo match for the one picked in the menu, with the bindings on the human
+    // paddles. Humans take the left paddle first.
+    fn start_match(&mut self, mut config: MatchConfig) {
+        config.seed = time_seed();
+        self.save_replay();
+        self.game_data = GameData::new(config);
+        self.mouse_control = false;
+        for i in 0..(config.players as usize).min(2) {
+            let controller = self.human_controller(i);
+            self.game_data.set_controller(i, controller);
+        }
+        self.recorder = Some(Recorder::new(config, self.timestep.tick_rate(), self.controls.clone()));
+        self.timestep.reset();
+        self.scene_state = SceneState::Playing;
     }
 
-    fn vertices(&self) -> &[f32] {
-        &self.vertices
+    // The server, if they're human, waiting on the countdown and just pressed Serve.
+    fn human_serving(&self, input_state: &InputState) -> Option<usize> {
+        let humans = self.game_data.config().players;
+        let server = self.game_data.server();
+        let waiting = self.game_data.state() == GameState::Starting && server < humans as usize;
+        let pressed = || self.controls.player_map(server, humans).just_pressed(Action::Serve, input_state);
+        (waiting && pressed()).then_some(server)
     }
 
-    fn apply_velocity(&mut self, delta: f32) {
-        self.position += self.velocity * delta;
-
-        self.clamp_position();
+    fn human_controller(&self, player: usize) -> Box<dyn PaddleController> {
+        let mouse = self.mouse_control && player == 0;
+        self.shared_input.controller(&self.controls, self.game_data.config().players, player, mouse)
     }
 
-    const MOVE_AMT: f32 = 0.02;
-    fn move_down(&mut self, delta: f32) {
-        // self.move_y(self.position.y - Self::MOVE_AMT);
-        if self.velocity.y > 0.0f32 {
-            self.velocity.y *= -1.0f32;
+    // Saves the match being recorded, if there is one and it got going.
+    fn save_replay(&mut self) {
+        let Some(recorder) = self.recorder.take() else {
+            return;
+        };
+        if recorder.ticks() == 0 {
+            return;
         }
-        self.apply_velocity(delta);
-    }
-
-    fn move_up(&mut self, delta: f32) {
-        // self.move_y(self.position.y + Self::MOVE_AMT);
-        if self.velocity.y < 0.0f32 {
-            self.velocity.y *= -1.0f32;
+        match recorder.finish().save(REPLAY_PATH) {
+            Ok(()) => println!("Saved the match to {}", REPLAY_PATH),
+            Err(err) => eprintln!("Failed to save {}: {}", REPLAY_PATH, err),
         }
-        self.apply_velocity(delta);
     }
 
-    fn move_y(&mut self, y_pos: f32) {
-        self.move_position(glm::Vec2::new(self.position.x, y_pos));
+    fn open_menu(&mut self) {
+        self.save_replay();
+        self.replay = None;
+        self.game_data = GameData::new(MatchConfig::new(0, MatchRules::default()));
+        self.timestep.reset();
+        self.menu.reset();
+        self.shown_menu = None;
+        self.scene_state = SceneState::Menu;
     }
 
-    fn move_position(&mut self, new_pos: glm::Vec2) {
-        self.position = new_pos;
-        self.clamp_position();
+    // Binds the first thing pressed to `action`, in place of whatever it had, and saves it.
+    fn finish_rebind(&mut self, input: &InputState, player: Option<usize>, action: Action) {
+        let Some(binding) = Binding::pressed(input).into_iter().next() else {
+            return;
+        };
+        self.controls.map_mut(player).rebind(action, binding);
+        if let Err(err) = self.controls.save(INPUT_CONFIG_PATH) {
+            eprintln!("Failed to save {}: {}", INPUT_CONFIG_PATH, err);
+        }
+        self.rebinding = None;
+        self.shown_menu = None;
+
+        // Rebinding from the pause menu changes the match being played too.
+        if let SceneState::Playing = self.scene_state {
+            for i in 0..(self.game_data.config().players as usize).min(2) {
+                let controller = self.human_controller(i);
+                self.game_data.set_controller(i, controller);
+            }
+            if let Some(recorder) = &mut self.recorder {
+                recorder.bind(player, action, self.controls.map(player).bindings(action));
+            }
+        }
     }
 
-    fn clamp_position(&mut self) {
-        self.position = clamp_position_2d(
-            self.position,
-            -1.0,
-            1.0,
-            -1.0,
-            1.0,
-            self.width / 2.0,
-            self.height / 2.0,
-        );
+    // The renderer draws the page's rows; the console names the picked one until we can draw text.
+    fn report_menu(&mut self) {
+        let page = self.menu.describe(&self.controls);
+        self.show_menu(page);
     }
 
-    // Maybe a better version would be to construct a list of surfaces from
-    // each object and just do collision resolution for each surface. Might have funny cases for
-    // corner hits and such that would make this interesting...
-    fn surface(&self, target_pos: glm::Vec2) -> Surface {
-        let direction = (target_pos.x - self.position.x).signum();
-        let y_offset = self.height / 2.0f32;
-        let x_offset = self.width / 2.0f32;
-
-        let mut a = glm::Vec2::new(0.0, 0.0);
-        let mut b = glm::Vec2::new(0.0, 0.0);
-
-        a.y = self.position.y - y_offset;
-        b.y = self.position.y + y_offset;
-
-        let x = self.position.x + (direction * x_offset);
-        a.x = x;
-        b.x = x;
-
-        Surface { a, b }
-    }
-}
-
-pub struct Ball {
-    id: u64,
-    radius: f32,
-    position: glm::Vec2,
-    velocity: glm::Vec2, // (speed, angle)
-    vertices: [f32;30],
-}
-
-static BALL_VERTICES: [f32;5] = [
-    0.0, 0.0, 1.0, 0.0, 0.0,
-];
-
-const DEFAULT_X_VELO: f32 = -1.0f32;
-const DEFAULT_Y_VELO: f32 = 0.3;
-// TODO: randomize starting y velo, randomize -1/+1 for starting x_velo
-impl Ball {
-    fn new(id: u64, radius: f32) -> Self {
-        Ball {
-            id,
-            radius,
-            position: glm::Vec2::new(0.0, 0.0),
-            velocity: glm::Vec2::new(DEFAULT_X_VELO, DEFAULT_Y_VELO),
-            vertices: QUAD_VERTICES.clone(),
+    fn show_menu(&mut self, page: String) {
+        if self.shown_menu.as_ref() != Some(&page) {
+            println!("{}", page);
+            self.shown_menu = Some(page);
         }
     }
 
-    fn reset(&mut self) {
-        self.position = glm::Vec2::new(0.0, 0.0);
-        self.velocity = glm::Vec2::new(DEFAULT_X_VELO, DEFAULT_Y_VELO);
+    // Reacts to what happened in the match. The demo behind the menu plays quietly, and the score
+    // is drawn with the court.
+    fn handle_event(&mut self, event: MatchEvent) {
+        if !matches!(self.scene_state, SceneState::Playing) {
+            return;
+        }
+        if let MatchEvent::PowerUpCollected { player, kind } = event {
+            println!("Player {} picked up {:?}", player + 1, kind);
+        }
     }
 
-    fn id(&self) -> u64 {
-        self.id
+    fn update_frames(&mut self) -> f32 {
+        let delta = self.frame_counter.increment();
+        match self.frame_counter.fps() {
+            Some(fps) => println!("{:.2} fps", fps),
+            None => { }
+        }
+        delta
     }
-    
-    fn vertices(&self) -> &[f32] {
-        &self.vertices
+}
+ 
+impl Drop for Game {
+    // Closing the window mid match still keeps the replay.
+    fn drop(&mut self) {
+        self.save_replay();
     }
+}
 
-    fn clamp_position(&mut self) {
-        self.position = clamp_position_2d(
-            self.position, 
-            -1.0,
-            1.0,
-            -1.0,
-            1.0,
-            self.radius,
-            self.radius
-        )
-    }
+enum SceneState {
+    Menu,           // Will handle menu navigation
+    Playing,        // Regular gameplay
+    Replay,         // Watching a replay
+}
 
-    fn clamp_velocity(&mut self) {
-        self.velocity = glm::clamp(&self.velocity, -2.0f32, 2.0f32);
-    }
+// Something different every run so AI mistakes aren't the same each match.
+fn time_seed() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |time| time.as_nanos() as u64)
+}
 
-    fn apply_velocity(&mut self, delta: f32) {
-        self.position += self.velocity * delta;
+// Either player's paddle controls move through the menu.
+fn menu_input(input: &InputState, controls: &InputConfig) -> Option<MenuInput> {
+    let moves = controls.players[0].merged(&controls.players[1]);
+    if moves.just_pressed(Action::MoveUp, input) {
+        Some(MenuInput::Up)
+    } else if moves.just_pressed(Action::MoveDown, input) {
+        Some(MenuInput::Down)
+    } else if controls.global.just_pressed(Action::Confirm, input) {
+        Some(MenuInput::Confirm)
+    } else if controls.global.just_pressed(Action::Back, input) {
+        Some(MenuInput::Back)
+    } else {
+        None
     }
-
-    
 }
 
-fn clamp_position_2d(pos: glm::Vec2, x_min: f32, x_max: f32, y_min: f32, y_max: f32, x_offset: f32, y_offset: f32) -> glm::Vec2 {
-    let mut out_pos = glm::Vec2::new(pos.x, pos.y);
-    if pos.y - y_offset < y_min {
-        out_pos.y = y_min + y_offset;
-    } else if pos.y + y_offset > y_max {
-        out_pos.y = y_max - y_offset;
+// The replay to watch if we were started with `--replay <path>`.
+fn replay_from_args() -> Option<ReplayPlayer> {
+    let args: Vec<String> = std::env::args().collect();
+    let path = args.iter().position(|arg| arg == "--replay").and_then(|i| args.get(i + 1))?;
+    match Replay::load(path) {
+        Ok(replay) => Some(ReplayPlayer::new(replay)),
+        Err(err) => {
+            eprintln!("Failed to load replay {}: {}", path, err);
+            None
+        },
     }
+}
 
-    if pos.x - x_offset < x_min {
-        out_pos.x = x_min + x_offset;
-    } else if pos.x + x_offset > x_max {
-        out_pos.x = x_max - x_offset;
+// The saved bindings, or the defaults if there aren't any yet or they can't be read.
+fn load_controls() -> InputConfig {
+    match InputConfig::load(INPUT_CONFIG_PATH) {
+        Ok(controls) => controls,
+        Err(ConfigError::Io(err)) if err.kind() == std::io::ErrorKind::NotFound => InputConfig::default(),
+        Err(err) => {
+            eprintln!("Failed to load {}, using the default controls: {}", INPUT_CONFIG_PATH, err);
+            InputConfig::default()
+        },
     }
-
-    out_pos
 }
 
-#[derive(Debug)]
-pub struct Renderer {
-    gl: Box<Context>,
+// How power-ups look on the court.
+fn power_up_color(kind: PowerUpKind) -> glm::Vec4 {
+    match kind {
+        PowerUpKind::Grow => glm::Vec4::new(0.3, 0.9, 0.3, 0.9),
+        PowerUpKind::Shrink => glm::Vec4::new(0.9, 0.3, 0.3, 0.9),
+        PowerUpKind::Split => glm::Vec4::new(1.0, 1.0, 1.0, 0.9),
+        PowerUpKind::SpeedBoost => glm::Vec4::new(1.0, 0.8, 0.2, 0.9),
+        PowerUpKind::Sticky => glm::Vec4::new(0.8, 0.4, 0.9, 0.9),
+        PowerUpKind::Shield => glm::Vec4::new(0.4, 0.7, 1.0, 0.9),
+    }
+}
 
+const COURT_COLOR: glm::Vec4 = glm::Vec4::new(0.2, 0.5, 0.2, 1.0);
+const WHITE: glm::Vec4 = glm::Vec4::new(1.0, 1.0, 1.0, 1.0);
+
+// Width of a shield bar, in world units.
+const SHIELD_WIDTH: f32 = 0.02;
+
+// Power-ups are rings this thick, as a fraction of their radius, so they don't pass for balls.
+const POWER_UP_THICKNESS: f32 = 0.4;
+// How far circles blend out at the edge, as a fraction of their radius.
+const CIRCLE_FADE: f32 = 0.1;
+
+// The net and the court's edges.
+const LINE_COLOR: glm::Vec4 = glm::Vec4::new(1.0, 1.0, 1.0, 0.5);
+const NET_DASHES: u32 = 15;
+
+// Countdown dots, in world units.
+const COUNTDOWN_PIP_SIZE: f32 = 0.06;
+const COUNTDOWN_PIP_SPACING: f32 = 0.12;
+const COUNTDOWN_PIP_Y: f32 = 0.5;
+
+// Score dots, in court units, and how many fit across half the court before wrapping.
+const SCORE_PIP_SIZE: f32 = 0.03;
+const SCORE_PIP_SPACING: f32 = 0.07;
+const SCORE_PIP_Y: f32 = 0.9;
+const SCORE_PIPS_PER_ROW: u32 = 11;
+const SCORE_COLOR: glm::Vec4 = glm::Vec4::new(1.0, 1.0, 1.0, 0.8);
+
+// Menu rows, in world units.
+const MENU_ROW_SIZE: glm::Vec2 = glm::Vec2::new(0.8, 0.1);
+const MENU_ROW_SPACING: f32 = 0.15;
+const MENU_ROW_COLOR: glm::Vec4 = glm::Vec4::new(1.0, 1.0, 1.0, 0.25);
+const MENU_PIP_SIZE: f32 = 0.04;
+const MENU_PIP_SPACING: f32 = 0.06;
+const MENU_PIP_COLOR: glm::Vec4 = glm::Vec4::new(0.0, 0.0, 0.0, 0.6);
+
+// Draws the match with `Renderer2D`. The court is stretched to fill the window along x, so
+// positions on the court are scaled by the aspect ratio and sizes aren't.
+pub struct CourtRenderer {
+    renderer: Renderer2D,
+    camera: camera::OrthographicCamera,
     width: i32,
     height: i32,
-
-    paddle_program: NativeProgram,
-    ball_program: NativeProgram,
-
-    paddle_data: HashMap<u64, (NativeBuffer, NativeVertexArray)>,
-    paddle_mvp: NativeUniformLocation,
-
-    ball_data: HashMap<u64, (NativeBuffer, NativeVertexArray)>,
-    ball_mvp: NativeUniformLocation,
-    ball_resolution: NativeUniformLocation,
-    ball_radius: NativeUniformLocation,
-    ball_center: NativeUniformLocation,
 }
 
-impl Renderer {
-    fn new<D: GlDisplay>(gl_display: &D, width: i32, height: i32, game_state: &GameData) -> Self {
-        unsafe{
-            let gl = Context::from_loader_function_cstr(
+impl CourtRenderer {
+    fn new<D: GlDisplay>(gl_display: &D, width: i32, height: i32) -> Self {
+        let gl = unsafe {
+            Context::from_loader_function_cstr(
                 |s| gl_display.get_proc_address(s)
-            );
-            gl.enable(PROGRAM_POINT_SIZE);
-            gl.viewport(0, 0, width, height);
-
-            let paddle_program = init_program(&gl, VERTEX_SHADER_SOURCE, FRAGMENT_SHADER_SOURCE);
-
-            gl.use_program(Some(paddle_program));
-            let pos_attrib = gl.get_attrib_location(paddle_program, "position")
-                .expect("Failed to find position location");
-            let col_attrib = gl.get_attrib_location(paddle_program, "color")
-                .expect("Failed to find color location");
-            let paddle_mvp = gl.get_uniform_location(paddle_program, "u_MVP")
-                .expect("Failed to find paddle mvp location");
-
-            let mut paddle_data = HashMap::new();
-            for player in game_state.players() {
-                let vertexes = create_paddle_buffer(
-                    &gl, pos_attrib, col_attrib, player.paddle.vertices()
-                );
-
-                paddle_data.insert(player.paddle.id(), vertexes);
-            }
+            )
+        };
+        let mut renderer = Renderer2D::new(gl, width, height);
+        renderer.set_clear_color(COURT_COLOR);
 
-            let ball_program = init_program(&gl, VERTEX_SHADER_SOURCE, BALL_FSHADER_SOURCE_V2);
-
-            gl.use_program(Some(ball_program));
-            let ball_pos = gl.get_attrib_location(ball_program, "position")
-                .expect("Failed to find ball position uniform");
-            let ball_col = gl.get_attrib_location(ball_program, "color")
-                .expect("Failed to find ball color uniform localtion");
-            let ball_radius = gl.get_uniform_location(ball_program, "u_BallRadius")
-                .expect("Failed to find ball radius uniform location");
-            let ball_mvp = gl.get_uniform_location(ball_program, "u_MVP")
-                .expect("Failed to find ball MVP uniform location");
-            let ball_resolution = gl.get_uniform_location(ball_program, "u_Resolution")
-                .expect("Failed to find screen resolution uniform location");
-            let ball_center = gl.get_uniform_location(ball_program, "u_Center")
-                .expect("Failed to find u_Center shader uniform");
-
-            let mut ball_data = HashMap::new();
-            let ball = game_state.ball(); 
-            let vertexes = create_paddle_buffer(
-                &gl, ball_pos, ball_col, ball.vertices()
-            );
-            ball_data.insert(ball.id(), vertexes);
-
-            gl.use_program(None);
-            Renderer {
-                gl: Box::new(gl),
-                width,
-                height,
-                paddle_program,
-                ball_program,
-                paddle_data,
-                paddle_mvp,
-                ball_data,
-                ball_mvp,
-                ball_radius,
-                ball_resolution,
-                ball_center,
-            }
+        CourtRenderer {
+            renderer,
+            camera: court_camera(width, height),
+            width,
+            height,
         }
     }
 
-    fn draw(&self, game_state: &GameData) { // TODO: take in ball and paddle from game state so we can draw accurately...
-        unsafe {
-            self.gl.clear(COLOR_BUFFER_BIT);
-            self.gl.clear_color(0.2, 0.5, 0.2, 1.0);
-
-            self.gl.use_program(Some(self.ball_program));
-            self.draw_ball(game_state.ball());
+    // `alpha` is how far we are between the last two simulation ticks. `menu` is drawn over
+    // everything else.
+    fn draw(&mut self, game_state: &GameData, alpha: f32, menu: Option<MenuView>) {
+        self.renderer.begin_scene(&self.camera);
 
-            self.gl.use_program(Some(self.paddle_program));
-            for player in game_state.players() {
-                self.draw_paddle(&player.paddle);
+        // Lines are drawn after everything else in a batch, so the court gets a batch of its own
+        // to stay under the paddles and balls.
+        self.draw_court();
+        self.renderer.next_batch();
 
-                // TODO: draw score, etc.
+        for power_up in game_state.power_ups() {
+            // Fades out over its last two seconds.
+            let mut color = power_up_color(power_up.kind);
+            color.w *= (power_up.time_left / 2.0f32).min(1.0f32);
+            self.draw_circle_on_court(&power_up.position, POWER_UP_RADIUS, color, POWER_UP_THICKNESS);
+        }
+        for effect in game_state.effects() {
+            if effect.kind == PowerUpKind::Shield {
+                // A bar along the goal line behind the paddle.
+                let x = if effect.player == 0 { COURT.min.x } else { COURT.max.x };
+                let size = glm::Vec2::new(SHIELD_WIDTH, COURT.max.y - COURT.min.y);
+                self.draw_on_court(&glm::Vec2::new(x, 0.0), &size, power_up_color(PowerUpKind::Shield));
             }
-
-            self.gl.use_program(None);
-            self.bind_vertex_array(None);
         }
-    }
 
-    unsafe fn draw_paddle(&self, paddle: &Paddle) {
-        unsafe {
-            if let Some((_, vao)) = self.paddle_data.get(&paddle.id()) {
-                let position = &paddle.position; // TODO: safe position access
+        for ball in game_state.balls() {
+            self.draw_circle_on_court(&ball.interpolated_position(alpha), ball.radius(), WHITE, 1.0f32);
+        }
 
-                let ratio: f32 = self.width as f32 / self.height as f32;
-                let pos = &glm::Vec3::new(position.x * ratio, position.y, 0.0);
+        for player in game_state.players() {
+            let paddle = player.paddle();
+            let size = glm::Vec2::new(paddle.width(), paddle.height());
+            self.draw_on_court(&paddle.interpolated_position(alpha), &size, WHITE);
+        }
+        self.draw_scores(game_state);
+
+        // `Renderer2D` draws a batch's quads before its circles, so the balls and power-ups have to
+        // be drawn before the overlay's quad goes in or they'd end up on top of it.
+        self.renderer.next_batch();
+
+        // Dim the court behind a menu, and fade back in over the countdown after a pause.
+        let dim = match game_state.state() {
+            _ if menu.is_some() => PAUSE_DIM,
+            GameState::Pause => PAUSE_DIM,
+            GameState::Resuming => PAUSE_DIM * game_state.countdown().unwrap_or(0.0f32) / RESUME_COUNTDOWN,
+            _ => 0.0f32,
+        };
+        if dim > 0.0f32 {
+            let ratio: f32 = self.width as f32 / self.height as f32;
+            let size = glm::Vec2::new(2.0f32 * ratio, 2.0f32);
+            self.renderer.draw_quad_ez(&glm::Vec3::zeros(), &size, glm::Vec4::new(0.0, 0.0, 0.0, dim));
+        }
 
-                let m = glm::translate(
-                    &glm::Mat4::identity(),
-                    pos,
-                );
+        // One dot above the centre spot for every second left to count down, over the overlay.
+        // TODO: draw the number once we can draw text.
+        self.renderer.next_batch();
+        if let Some(left) = game_state.countdown() {
+            let seconds = left.ceil() as u32;
+            let size = glm::Vec2::new(COUNTDOWN_PIP_SIZE, COUNTDOWN_PIP_SIZE);
+            for i in 0..seconds {
+                let x = (i as f32 - (seconds - 1) as f32 / 2.0f32) * COUNTDOWN_PIP_SPACING;
+                let transform = glm::translate(&glm::Mat4::identity(), &glm::Vec3::new(x, COUNTDOWN_PIP_Y, 0.0));
+                let transform = glm::scale(&transform, &glm::vec2_to_vec3(&size));
+                self.renderer.draw_circle(&transform, glm::Vec4::new(1.0, 1.0, 1.0, 0.8), 1.0f32, CIRCLE_FADE, -1);
+            }
+        }
+        if let Some(menu) = menu {
+            self.draw_menu(menu);
+        }
 
-                let p = self.ortho_2d(ratio);
-                let mvp = p * m;
+        self.renderer.end_scene();
+    }
+
+    // A dot for every point at the top of each player's half, counting out from the net and
+    // wrapping onto another row past `SCORE_PIPS_PER_ROW`, with a dot for every set won below.
+    // The winner's points are underlined once the match is over.
+    fn draw_scores(&mut self, game_state: &GameData) {
+        let ratio: f32 = self.width as f32 / self.height as f32;
+        let pip = glm::Vec2::new(SCORE_PIP_SIZE, SCORE_PIP_SIZE);
+        let scores = game_state.scores();
+        let sets = game_state.sets();
+        for player in 0..2 {
+            let side = if player == 0 { -1.0f32 } else { 1.0f32 };
+            let x = |i: u32| side * (SCORE_PIP_SPACING * (i % SCORE_PIPS_PER_ROW + 1) as f32);
+            for i in 0..scores[player] {
+                let y = SCORE_PIP_Y - (i / SCORE_PIPS_PER_ROW) as f32 * SCORE_PIP_SPACING;
+                self.draw_on_court(&glm::Vec2::new(x(i), y), &pip, SCORE_COLOR);
+            }
 
-                self.gl.uniform_matrix_4_f32_slice(Some(&self.paddle_mvp), false, mvp.as_slice());
-                self.gl.bind_vertex_array(Some(*vao));
-                self.gl.draw_arrays(TRIANGLES, 0, 6);
+            let rows = scores[player].max(1).div_ceil(SCORE_PIPS_PER_ROW);
+            let below = SCORE_PIP_Y - rows as f32 * SCORE_PIP_SPACING;
+            if game_state.rules().best_of > 1 {
+                for i in 0..sets[player] {
+                    let position = glm::Vec2::new(x(i), below);
+                    self.draw_circle_on_court(&position, SCORE_PIP_SIZE / 2.0f32, SCORE_COLOR, 1.0f32);
+                }
+            }
+            if game_state.winner() == Some(player) {
+                // Sizes aren't stretched with the court, so the line is worked out in world units.
+                let dots = scores[player].clamp(1, SCORE_PIPS_PER_ROW) as f32;
+                let x = side * SCORE_PIP_SPACING * (dots + 1.0f32) / 2.0f32 * ratio;
+                let size = glm::Vec2::new(SCORE_PIP_SPACING * dots * ratio, SCORE_PIP_SIZE / 4.0f32);
+                let y = below + SCORE_PIP_SPACING / 2.0f32;
+                self.renderer.draw_quad_ez(&glm::Vec3::new(x, y, 0.0), &size, WHITE);
             }
         }
     }
 
-    unsafe fn draw_ball(&self, ball: &Ball) {
-        unsafe {
-            if let Some((_, vao)) = self.ball_data.get(&ball.id()) {
-                let position = &ball.position; // TODO: safe position access
-                let ratio: f32 = self.width as f32 / self.height as f32; // TODO: only do this
-                                                                         // once per loop, pass
-                                                                         // as param.
-                let pos = &glm::Vec3::new(position.x * ratio, position.y, 0.0);
-
-                let m = glm::translate(
-                    &glm::Mat4::identity(),
-                    pos,
-                );
-
-                let p = self.ortho_2d(ratio);
-                // let mvp = p * glm::Mat4::identity();
-                let mvp = p * m;
-
-                self.gl.uniform_matrix_4_f32_slice(Some(&self.ball_mvp), false, mvp.as_slice());
-                self.gl.uniform_1_f32(Some(&self.ball_radius), ball.radius);
-                self.gl.uniform_2_f32(Some(&self.ball_resolution), self.width as f32, self.height as f32);
-                self.gl.uniform_2_f32(Some(&self.ball_center), pos.x, pos.y);
-                self.gl.bind_vertex_array(Some(*vao));
-                self.gl.draw_arrays(TRIANGLES, 0, 6);
+    // A bar for each row of the menu, stacked around the middle of the window, with the picked
+    // one lit up and marked on its left. Each row carries its number in dots until we can draw
+    // text.
+    fn draw_menu(&mut self, menu: MenuView) {
+        let top = (menu.rows - 1) as f32 / 2.0f32 * MENU_ROW_SPACING;
+        let pip = glm::Vec2::new(MENU_PIP_SIZE, MENU_PIP_SIZE);
+        for row in 0..menu.rows {
+            let y = top - row as f32 * MENU_ROW_SPACING;
+            let left = -MENU_ROW_SIZE.x / 2.0f32;
+            if row == menu.selected {
+                self.renderer.draw_quad_ez(&glm::Vec3::new(0.0, y, 0.0), &MENU_ROW_SIZE, WHITE);
+                self.renderer.draw_quad_ez(&glm::Vec3::new(left - MENU_PIP_SPACING, y, 0.0), &pip, WHITE);
+            } else {
+                self.renderer.draw_quad_ez(&glm::Vec3::new(0.0, y, 0.0), &MENU_ROW_SIZE, MENU_ROW_COLOR);
+            }
+            for i in 0..=row {
+                let x = left + (i + 1) as f32 * MENU_PIP_SPACING;
+                self.renderer.draw_quad_ez(&glm::Vec3::new(x, y, 0.0), &pip, MENU_PIP_COLOR);
             }
         }
     }
 
-    fn ortho_2d(&self, aspect_ratio: f32) -> glm::Mat4 {
-        glm::ortho(
-            -aspect_ratio,
-            aspect_ratio,
-            -1.0,
-            1.0,
-            1.0,
-            -1.0,
-        )
-    }
-
-    fn resize(&mut self, width: i32, height: i32) {
-        unsafe {
-            self.width = width;
-            self.height = height;
-            self.gl.viewport(0, 0, width, height);
+    // A dashed net down the middle and a line around the court.
+    fn draw_court(&mut self) {
+        let ratio: f32 = self.width as f32 / self.height as f32;
+        let height = COURT.max.y - COURT.min.y;
+        let dash = height / (2 * NET_DASHES) as f32;
+        for i in 0..NET_DASHES {
+            let y = COURT.min.y + (2 * i) as f32 * dash + dash / 2.0f32;
+            self.renderer.draw_line(&glm::Vec3::new(0.0, y, 0.0), &glm::Vec3::new(0.0, y + dash, 0.0), LINE_COLOR);
         }
-    }
-}
 
-impl Deref for Renderer {
-    type Target = Context;
-    fn deref(&self) -> &Self::Target {
-        &self.gl
+        let center = (COURT.min + COURT.max) / 2.0f32;
+        let transform = glm::translate(&glm::Mat4::identity(), &glm::Vec3::new(center.x * ratio, center.y, 0.0));
+        let transform = glm::scale(&transform, &glm::Vec3::new((COURT.max.x - COURT.min.x) * ratio, height, 1.0));
+        self.renderer.draw_rect_outline(&transform, LINE_COLOR);
     }
-}
 
-impl Drop for Renderer {
-    fn drop(&mut self) {
-        unsafe {
-            self.gl.delete_program(self.paddle_program);
-            self.gl.delete_program(self.ball_program);
-
-            for  (_, (vbo, vao)) in self.paddle_data.iter() {
-                self.gl.delete_buffer(*vbo);
-                self.gl.delete_vertex_array(*vao);
-            }
-            self.paddle_data.clear();
-        }
+    // A `size` rectangle centred on `position` on the court.
+    fn draw_on_court(&mut self, position: &glm::Vec2, size: &glm::Vec2, color: glm::Vec4) {
+        let ratio: f32 = self.width as f32 / self.height as f32;
+        let position = glm::Vec3::new(position.x * ratio, position.y, 0.0);
+        self.renderer.draw_quad_ez(&position, size, color);
     }
-}
-
-unsafe fn init_program(gl: &Context, vshader_src: &str, fshader_src: &str) -> NativeProgram {
-    let program = gl.create_program().expect("Failed to create gl program");
-
-    let vertex_shader = init_shader(&gl, glow::VERTEX_SHADER, vshader_src);
-    let frag_shader = init_shader(&gl, glow::FRAGMENT_SHADER, fshader_src);
 
-    gl.attach_shader(program, vertex_shader);
-    gl.attach_shader(program, frag_shader);
-    gl.link_program(program);
-    if !gl.get_program_link_status(program) {
-        panic!("{}", gl.get_program_info_log(program));
+    // A circle of `radius` centred on `position` on the court.
+    fn draw_circle_on_court(&mut self, position: &glm::Vec2, radius: f32, color: glm::Vec4, thickness: f32) {
+        let ratio: f32 = self.width as f32 / self.height as f32;
+        let transform = glm::translate(&glm::Mat4::identity(), &glm::Vec3::new(position.x * ratio, position.y, 0.0));
+        let transform = glm::scale(&transform, &glm::Vec3::new(2.0f32 * radius, 2.0f32 * radius, 1.0f32));
+        self.renderer.draw_circle(&transform, color, thickness, CIRCLE_FADE, -1);
     }
-    gl.delete_shader(vertex_shader);
-    gl.delete_shader(frag_shader);
-
-    program
-}
-
-unsafe fn init_shader(gl: &Context, shader_type: u32, shader_source: &str) -> Shader {
-    let shader: Shader = gl.create_shader(shader_type)
-        .expect(&format!("Failed to create shader: {}", shader_type));
 
-    gl.shader_source(shader, shader_source);
-    gl.compile_shader(shader);
-
-    if !gl.get_shader_compile_status(shader) {
-        panic!("{}\n{}", gl.get_shader_info_log(shader), shader_source);
-    } else {
-        shader
+    fn resize(&mut self, width: i32, height: i32) {
+        self.width = width;
+        self.height = height;
+        self.camera = court_camera(width, height);
+        self.renderer.resize(width, height);
     }
-}
-
-unsafe fn create_paddle_buffer(gl: &Context, pos_loc: u32, col_loc: u32, vertices: &[f32]) -> (NativeBuffer, NativeVertexArray) {
-    let bytes: &[u8] = core::slice::from_raw_parts(
-        vertices.as_ptr() as *const u8,
-        vertices.len() * core::mem::size_of::<f32>()
-    );
-
-    let vbo = gl.create_buffer()
-        .expect("Failed to create vertex buffer object");
-    let vao = gl.create_vertex_array()
-        .expect("Failed to create vertex array object");
-
-    gl.bind_buffer(ARRAY_BUFFER, Some(vbo));
-
-
-    gl.bind_vertex_array(Some(vao));
-
-    gl.enable_vertex_attrib_array(pos_loc);
-    gl.vertex_attrib_pointer_f32(
-        pos_loc, 
-        2, 
-        FLOAT, 
-        false, 
-        std::mem::size_of::<[f32;5]>() as i32, 
-        0
-    );
-
-    gl.enable_vertex_attrib_array(col_loc);
-    gl.vertex_attrib_pointer_f32(
-        col_loc, 
-        3, 
-        FLOAT, 
-        false, 
-        std::mem::size_of::<[f32;5]>() as i32, 
-        std::mem::size_of::<[f32;2]>() as i32, 
-
-    ); 
-
-    gl.buffer_data_u8_slice(ARRAY_BUFFER, bytes, STATIC_DRAW);
-    gl.bind_vertex_array(None);
-    gl.bind_buffer(ARRAY_BUFFER, None);
-    (vbo, vao)
-}
-
-const VERTEX_SHADER_SOURCE: &str = "
-#version 330 core
-precision mediump float;
-
-uniform mat4 u_MVP;
-
-attribute vec2 position;
-attribute vec3 color;
-
-out vec3 v_color;
-
-void main() {
-    gl_Position = u_MVP * vec4(position, 0.0, 1.0);
-    v_color = color;
-}
-\0";
-
-const FRAGMENT_SHADER_SOURCE: &str = "
-#version 330 core
-precision mediump float;
 
-in vec3 v_color;
-
-void main() {
-    gl_FragColor = vec4(v_color, 1.0);
-}
-\0";
-
-const BALL_FSHADER_SOURCE: &str = "
-#version 100
-precision mediump float;
-
-varying vec3 v_color;
-
-void main() {
-    vec2 coord = gl_PointCoord * 2.0 - 1.0;
-    float dist = length(coord);
-    float alpha = smoothstep(1.0, 0.98, 1.0 - dist); // Soft edge (anti-aliasing)
-
-    if (dist > 1.0)
-        discard;
-
-    gl_FragColor = vec4(v_color, alpha);
-}
-\0";
-
-const BALL_FSHADER_SOURCE_V2: &str = "
-#version 330 core
-precision mediump float;
-
-uniform float u_BallRadius;
-uniform vec2 u_Resolution;
-uniform vec2 u_Center;
-
-in vec3 v_color;
-// in vec2 v_pos;
-
-void main() {
-    vec2 uv = (gl_FragCoord.xy / u_Resolution.xy) * 2.0 - 1.0;
-    float aspect = u_Resolution.x / u_Resolution.y;
-    uv.x *= aspect;
-    // float dist = length(uv);
-    float dist = distance(u_Center, uv);
-    float alpha = smoothstep(1.0, 0.98, 1.0 - dist); // Soft edge (anti-aliasing)
-
-    if (dist > u_BallRadius)
-        discard;
-    
-    gl_FragColor = vec4(v_color, alpha);
+    // Where a cursor position in window pixels is on the court. The court is drawn stretched by
+    // the aspect ratio along x, so that's undone after the camera.
+    fn cursor_to_court(&self, cursor: &glm::Vec2) -> glm::Vec2 {
+        let viewport = glm::Vec2::new(self.width as f32, self.height as f32);
+        let world = self.camera.screen_to_world(cursor, &viewport);
+        let ratio: f32 = self.width as f32 / self.height as f32;
+        glm::Vec2::new(world.x / ratio, world.y)
+    }
 }
-\0";
-
-
 
-
-const PONG_SHADER_SOURCE: &str = "
-#version 300 es
- 
-precision highp float;
-precision highp sample2D;
-
-in vec2 uv;
-out vec4 out_color;
-
-uniform vec2 u_resolution;
-uniform float u_time;
-uniform vec4 u_mouse;
-
-uniform vec2 u_rectLeftPos;
-uniform vec3 u_rectLeftColor;
-uniform vec2 u_rectRightPos;
-uniform vec3 u_rectRightColor;
-uniform vec2 u_ballPos;
-uniform float u_ballSize;
-
-void main() {
-    vec2 st = uv * vec2(u_resolution.x / u_resolution.y, 1.);
-
-    float dist = distance(st, ballPos);
-    if (dist <= ballSize) { // ball pixel
-        out_color = vec4(1.0, 1.0, 1.0, 1.0) // white ball, could parameterize this
-    } else if ((st.x >= rectPos.x && // paddle pixel
-                st.x <= rectPos.x + rectSize.x &&
-                st.y >= rectPos.y && st.y <= rectPos.y + rectSize.y) ||
-               (st.x >= rightRectPos.x && 
-                st.x <= rightRectPos.x + rightRectSIze.x &&
-                st.y >= rightRectPos.y && st.y <= rightRectPos.y + rightRectSIze.y)) {
-        out_color = vec4(1.0, 1.0, 1.0, 1.0); // white
-    } else { // background pixel
-        out_color = vec4(0.0, 0.0, 0.0, 0.0);
-    }
+// Shows the whole court, -1 to 1 high and as wide as the window allows.
+fn court_camera(width: i32, height: i32) -> camera::OrthographicCamera {
+    let ratio: f32 = width as f32 / height as f32;
+    camera::OrthographicCamera::new(-ratio, ratio, -1.0f32, 1.0f32)
 }
-\0";
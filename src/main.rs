@@ -1,8 +1,12 @@
 pub mod core;
 pub mod app;
+pub mod controls;
 pub mod renderer;
 pub mod game;
+pub mod menu;
 pub mod physics;
+pub mod replay;
+pub mod sim;
 
 use crate::app::App;
 
@@ -15,6 +19,16 @@ use glutin::{config::ConfigTemplateBuilder, context::PossiblyCurrentContext, pre
 
 
 fn main() -> Result<(), Box<dyn Error>> {
+    // `--dump-replay <path>` plays a replay without a window and prints how the match ended, so
+    // runs can be diffed.
+    let args: Vec<String> = std::env::args().collect();
+    if let [_, flag, path] = &args[..] && flag == "--dump-replay" {
+        let mut player = replay::ReplayPlayer::new(replay::Replay::load(path)?);
+        player.run_to_end();
+        println!("{}", replay::dump_state(player.game()));
+        return Ok(());
+    }
+
     let config_template = ConfigTemplateBuilder::new().with_alpha_size(8).with_transparency(false);
     let mut app = App::new(config_template);
 
@@ -0,0 +1,435 @@
+// The main menu (title, player select, difficulty, target score and controls) and the pause menu.
+// Nothing in here draws or reads the keyboard; the game feeds them `MenuInput`s and acts on the
+// events they hand back.
+use crate::core::action::{Action, Binding, InputConfig};
+use crate::sim::{AiDifficulty, GameMode, MatchConfig, MatchRules};
+
+/// Points to win a set, in the order the menu cycles through them.
+pub const TARGET_SCORES: [u32; 3] = [5, 11, 21];
+
+const DIFFICULTIES: [AiDifficulty; 3] = [AiDifficulty::Easy, AiDifficulty::Medium, AiDifficulty::Hard];
+
+const TITLE_OPTIONS: [&str; 3] = ["Play", "Controls", "Arcade"];
+
+const PAUSE_OPTIONS: [&str; 4] = ["Resume", "Restart match", "Settings", "Quit to menu"];
+
+/// The rows of the controls page: which player's bindings (`None` for menus and pausing) and
+/// which action.
+pub const CONTROL_ROWS: [(Option<usize>, Action); 9] = [
+    (None, Action::Pause),
+    (None, Action::Confirm),
+    (None, Action::Back),
+    (Some(0), Action::MoveUp),
+    (Some(0), Action::MoveDown),
+    (Some(0), Action::Serve),
+    (Some(1), Action::MoveUp),
+    (Some(1), Action::MoveDown),
+    (Some(1), Action::Serve),
+];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MenuState {
+    Title,
+    PlayerSelect,
+    Difficulty,  // skipped when nobody plays against the computer
+    TargetScore,
+    Controls,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MenuInput {
+    Up,      // previous option
+    Down,    // next option
+    Confirm, // on to the next page, or start the match from the last one
+    Back,    // back to the previous page
+}
+
+/// What a page looks like without its words: how many rows it has and which one is picked. The
+/// renderer draws menus from this until it can draw text.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MenuView {
+    pub rows: usize,
+    pub selected: usize,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MenuEvent {
+    Start(MatchConfig),
+    /// The player wants to rebind `action`; the next thing they press should become its binding.
+    Rebind { player: Option<usize>, action: Action },
+}
+
+pub struct Menu {
+    state: MenuState,
+    title_option: usize, // index into `TITLE_OPTIONS`
+    control_row: usize,  // index into `CONTROL_ROWS`
+    mode: GameMode, // picked on the title page
+    players: u32, // human players, 0 to 2
+    difficulty: usize, // index into `DIFFICULTIES`
+    target_score: usize, // index into `TARGET_SCORES`
+}
+
+impl Menu {
+    pub fn new() -> Self {
+        Menu {
+            state: MenuState::Title,
+            title_option: 0,
+            control_row: 0,
+            mode: GameMode::Classic,
+            players: 1,
+            difficulty: 1,
+            target_score: 1,
+        }
+    }
+
+    pub fn state(&self) -> MenuState {
+        self.state
+    }
+
+    pub fn mode(&self) -> GameMode {
+        self.mode
+    }
+
+    pub fn players(&self) -> u32 {
+        self.players
+    }
+
+    pub fn difficulty(&self) -> AiDifficulty {
+        DIFFICULTIES[self.difficulty]
+    }
+
+    pub fn target_score(&self) -> u32 {
+        TARGET_SCORES[self.target_score]
+    }
+
+    /// The current page's rows. The player select rows are 0, 1 and 2 players.
+    pub fn view(&self) -> MenuView {
+        let (rows, selected) = match self.state {
+            MenuState::Title => (TITLE_OPTIONS.len(), self.title_option),
+            MenuState::Controls => (CONTROL_ROWS.len(), self.control_row),
+            MenuState::PlayerSelect => (3, self.players as usize),
+            MenuState::Difficulty => (DIFFICULTIES.len(), self.difficulty),
+            MenuState::TargetScore => (TARGET_SCORES.len(), self.target_score),
+        };
+        MenuView { rows, selected }
+    }
+
+    /// Goes back to the title page, keeping the options picked last time.
+    pub fn reset(&mut self) {
+        self.state = MenuState::Title;
+    }
+
+    /// The match to play with the options picked so far.
+    pub fn config(&self) -> MatchConfig {
+        MatchConfig {
+            difficulty: self.difficulty(),
+            mode: self.mode,
+            ..MatchConfig::new(self.players, MatchRules::first_to(self.target_score(), 2))
+        }
+    }
+
+    /// Applies one input. Returns an event when the player starts a match or picks a binding to
+    /// change.
+    pub fn handle(&mut self, input: MenuInput) -> Option<MenuEvent> {
+        match input {
+            MenuInput::Up => self.step_option(-1),
+            MenuInput::Down => self.step_option(1),
+            MenuInput::Confirm => {
+                match self.state {
+                    MenuState::TargetScore => return Some(MenuEvent::Start(self.config())),
+                    MenuState::Controls => {
+                        let (player, action) = CONTROL_ROWS[self.control_row];
+                        return Some(MenuEvent::Rebind { player, action });
+                    },
+                    MenuState::Title => {
+                        self.mode = if self.title_option == 2 { GameMode::Arcade } else { GameMode::Classic };
+                        self.state = self.next_page();
+                    },
+                    _ => self.state = self.next_page(),
+                }
+            },
+            MenuInput::Back => self.state = self.previous_page(),
+        }
+        None
+    }
+
+    // Cycles the option on the current page `step` places forwards or backwards.
+    fn step_option(&mut self, step: i32) {
+        fn cycle(value: usize, len: usize, step: i32) -> usize {
+            (value as i32 + step).rem_euclid(len as i32) as usize
+        }
+
+        match self.state {
+            MenuState::Title => self.title_option = cycle(self.title_option, TITLE_OPTIONS.len(), step),
+            MenuState::Controls => self.control_row = cycle(self.control_row, CONTROL_ROWS.len(), step),
+            MenuState::PlayerSelect => self.players = cycle(self.players as usize, 3, step) as u32,
+            MenuState::Difficulty => self.difficulty = cycle(self.difficulty, DIFFICULTIES.len(), step),
+            MenuState::TargetScore => self.target_score = cycle(self.target_score, TARGET_SCORES.len(), step),
+        }
+    }
+
+    fn next_page(&self) -> MenuState {
+        match self.state {
+            MenuState::Title if self.title_option == 1 => MenuState::Controls,
+            MenuState::Title => MenuState::PlayerSelect,
+            MenuState::Controls => MenuState::Controls,
+            MenuState::PlayerSelect if self.players >= 2 => MenuState::TargetScore,
+            MenuState::PlayerSelect => MenuState::Difficulty,
+            MenuState::Difficulty | MenuState::TargetScore => MenuState::TargetScore,
+        }
+    }
+
+    fn previous_page(&self) -> MenuState {
+        match self.state {
+            MenuState::Title | MenuState::PlayerSelect | MenuState::Controls => MenuState::Title,
+            MenuState::Difficulty => MenuState::PlayerSelect,
+            MenuState::TargetScore if self.players >= 2 => MenuState::PlayerSelect,
+            MenuState::TargetScore => MenuState::Difficulty,
+        }
+    }
+
+    /// One line describing the current page, for the console until we can draw text.
+    pub fn describe(&self, controls: &InputConfig) -> String {
+        match self.state {
+            MenuState::Title => format!("GL PONG: < {} >", TITLE_OPTIONS[self.title_option]),
+            MenuState::Controls => describe_control(controls, self.control_row),
+            MenuState::PlayerSelect => format!("Players: < {} >", self.players),
+            MenuState::Difficulty => format!("Difficulty: < {:?} >", self.difficulty()),
+            MenuState::TargetScore => format!("First to: < {} > - Enter to start", self.target_score()),
+        }
+    }
+}
+
+impl Default for Menu {
+    fn default() -> Self {
+        Menu::new()
+    }
+}
+
+// One row of `CONTROL_ROWS` with what's bound to it.
+fn describe_control(controls: &InputConfig, row: usize) -> String {
+    let (player, action) = CONTROL_ROWS[row];
+    let bindings: Vec<_> = controls.map(player).bindings(action).iter().map(Binding::to_string).collect();
+    let who = player.map_or("Any".to_string(), |player| format!("Player {}", player + 1));
+    format!("{} {}: < {} > - Enter to rebind", who, action.name(), bindings.join(", "))
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PausePage {
+    Options,  // resume, restart, settings, quit
+    Settings, // mouse paddle and controls
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PauseEvent {
+    Resume,
+    Restart,
+    Quit,
+    /// Switch the first player's paddle between the mouse and their bindings.
+    ToggleMouse,
+    /// The player wants to rebind `action`; the next thing they press should become its binding.
+    Rebind { player: Option<usize>, action: Action },
+}
+
+/// The menu over a paused match. The settings page has the mouse paddle switch on its first row
+/// and then the same rows as the controls page.
+pub struct PauseMenu {
+    page: PausePage,
+    option: usize, // index into `PAUSE_OPTIONS`
+    setting: usize, // 0 for the mouse, then 1 past an index into `CONTROL_ROWS`
+}
+
+impl PauseMenu {
+    pub fn new() -> Self {
+        PauseMenu { page: PausePage::Options, option: 0, setting: 0 }
+    }
+
+    pub fn page(&self) -> PausePage {
+        self.page
+    }
+
+    pub fn view(&self) -> MenuView {
+        match self.page {
+            PausePage::Options => MenuView { rows: PAUSE_OPTIONS.len(), selected: self.option },
+            PausePage::Settings => MenuView { rows: CONTROL_ROWS.len() + 1, selected: self.setting },
+        }
+    }
+
+    /// Back to the first option, for the next time the match is paused.
+    pub fn reset(&mut self) {
+        self.page = PausePage::Options;
+        self.option = 0;
+    }
+
+    /// Applies one input. Back from the options resumes, the same as picking Resume.
+    pub fn handle(&mut self, input: MenuInput) -> Option<PauseEvent> {
+        let step = match input {
+            MenuInput::Up => -1,
+            MenuInput::Down => 1,
+            MenuInput::Confirm => return self.confirm(),
+            MenuInput::Back => {
+                return match self.page {
+                    PausePage::Options => Some(PauseEvent::Resume),
+                    PausePage::Settings => {
+                        self.page = PausePage::Options;
+                        None
+                    },
+                };
+            },
+        };
+        match self.page {
+            PausePage::Options => self.option = (self.option as i32 + step).rem_euclid(PAUSE_OPTIONS.len() as i32) as usize,
+            PausePage::Settings => self.setting = (self.setting as i32 + step).rem_euclid(CONTROL_ROWS.len() as i32 + 1) as usize,
+        }
+        None
+    }
+
+    fn confirm(&mut self) -> Option<PauseEvent> {
+        match self.page {
+            PausePage::Options => match self.option {
+                0 => Some(PauseEvent::Resume),
+                1 => Some(PauseEvent::Restart),
+                2 => {
+                    self.page = PausePage::Settings;
+                    None
+                },
+                _ => Some(PauseEvent::Quit),
+            },
+            PausePage::Settings if self.setting == 0 => Some(PauseEvent::ToggleMouse),
+            PausePage::Settings => {
+                let (player, action) = CONTROL_ROWS[self.setting - 1];
+                Some(PauseEvent::Rebind { player, action })
+            },
+        }
+    }
+
+    /// One line describing the current page, for the console until we can draw text.
+    pub fn describe(&self, controls: &InputConfig, mouse: bool) -> String {
+        match self.page {
+            PausePage::Options => format!("PAUSED: < {} >", PAUSE_OPTIONS[self.option]),
+            PausePage::Settings if self.setting == 0 => {
+                format!("Mouse paddle: < {} > - Enter to switch", if mouse { "On" } else { "Off" })
+            },
+            PausePage::Settings => describe_control(controls, self.setting - 1),
+        }
+    }
+}
+
+impl Default for PauseMenu {
+    fn default() -> Self {
+        PauseMenu::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builds_the_chosen_match() {
+        let mut menu = Menu::new();
+        assert_eq!(None, menu.handle(MenuInput::Confirm));
+        assert_eq!(MenuState::PlayerSelect, menu.state());
+        menu.handle(MenuInput::Up);
+        assert_eq!(0, menu.players());
+        assert_eq!(MenuView { rows: 3, selected: 0 }, menu.view());
+
+        menu.handle(MenuInput::Confirm);
+        assert_eq!(MenuState::Difficulty, menu.state());
+        menu.handle(MenuInput::Down);
+        menu.handle(MenuInput::Confirm);
+        menu.handle(MenuInput::Down);
+
+        let Some(MenuEvent::Start(config)) = menu.handle(MenuInput::Confirm) else {
+            panic!("expected the match to start");
+        };
+        assert_eq!(0, config.players);
+        assert_eq!(AiDifficulty::Hard, config.difficulty);
+        assert_eq!(21, config.rules.target_score);
+    }
+
+    #[test]
+    fn arcade_is_picked_from_the_title() {
+        let mut menu = Menu::new();
+        menu.handle(MenuInput::Up);
+        menu.handle(MenuInput::Confirm);
+        assert_eq!(MenuState::PlayerSelect, menu.state());
+        assert_eq!(GameMode::Arcade, menu.mode());
+        menu.handle(MenuInput::Confirm);
+        menu.handle(MenuInput::Confirm);
+        let Some(MenuEvent::Start(config)) = menu.handle(MenuInput::Confirm) else {
+            panic!("expected the match to start");
+        };
+        assert_eq!(GameMode::Arcade, config.mode);
+
+        menu.reset();
+        menu.handle(MenuInput::Down);
+        menu.handle(MenuInput::Confirm);
+        assert_eq!(GameMode::Classic, menu.mode());
+    }
+
+    #[test]
+    fn two_players_skip_difficulty() {
+        let mut menu = Menu::new();
+        menu.handle(MenuInput::Confirm);
+        menu.handle(MenuInput::Down);
+        assert_eq!(2, menu.players());
+        menu.handle(MenuInput::Confirm);
+        assert_eq!(MenuState::TargetScore, menu.state());
+        menu.handle(MenuInput::Back);
+        assert_eq!(MenuState::PlayerSelect, menu.state());
+        menu.handle(MenuInput::Down);
+        assert_eq!(0, menu.players());
+    }
+
+    #[test]
+    fn controls_page_asks_for_rebinds() {
+        let mut menu = Menu::new();
+        menu.handle(MenuInput::Down);
+        menu.handle(MenuInput::Confirm);
+        assert_eq!(MenuState::Controls, menu.state());
+        menu.handle(MenuInput::Up);
+        assert_eq!(
+            Some(MenuEvent::Rebind { player: Some(1), action: Action::Serve }),
+            menu.handle(MenuInput::Confirm)
+        );
+        assert!(menu.describe(&InputConfig::default()).contains("physical:Slash"));
+        menu.handle(MenuInput::Back);
+        assert_eq!(MenuState::Title, menu.state());
+    }
+
+    #[test]
+    fn pause_menu_picks_options() {
+        let mut pause = PauseMenu::new();
+        assert_eq!(Some(PauseEvent::Resume), pause.handle(MenuInput::Confirm));
+        assert_eq!(Some(PauseEvent::Resume), pause.handle(MenuInput::Back));
+        pause.handle(MenuInput::Down);
+        assert_eq!(Some(PauseEvent::Restart), pause.handle(MenuInput::Confirm));
+        pause.handle(MenuInput::Down);
+        pause.handle(MenuInput::Down);
+        assert_eq!(MenuView { rows: 4, selected: 3 }, pause.view());
+        assert_eq!(Some(PauseEvent::Quit), pause.handle(MenuInput::Confirm));
+        pause.reset();
+        assert_eq!(MenuView { rows: 4, selected: 0 }, pause.view());
+        assert_eq!(Some(PauseEvent::Resume), pause.handle(MenuInput::Confirm));
+    }
+
+    #[test]
+    fn pause_settings_switch_mouse_and_rebind() {
+        let mut pause = PauseMenu::new();
+        pause.handle(MenuInput::Up);
+        pause.handle(MenuInput::Up);
+        assert_eq!(None, pause.handle(MenuInput::Confirm));
+        assert_eq!(PausePage::Settings, pause.page());
+        assert_eq!(MenuView { rows: CONTROL_ROWS.len() + 1, selected: 0 }, pause.view());
+        assert!(pause.describe(&InputConfig::default(), true).contains("On"));
+        assert_eq!(Some(PauseEvent::ToggleMouse), pause.handle(MenuInput::Confirm));
+        pause.handle(MenuInput::Down);
+        assert_eq!(
+            Some(PauseEvent::Rebind { player: None, action: Action::Pause }),
+            pause.handle(MenuInput::Confirm)
+        );
+        assert_eq!(None, pause.handle(MenuInput::Back));
+        assert_eq!(PausePage::Options, pause.page());
+    }
+}
@@ -1,27 +1,644 @@
+// Small 2D collision library: segments, axis aligned boxes, circles and capsules, with overlap
+// tests, closest point queries, contact manifolds and swept circle tests.
 use nalgebra_glm as glm;
 
-#[derive(Debug)]
+/// A line segment from `a` to `b`.
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub struct Surface {
     pub a: glm::Vec2,
     pub b: glm::Vec2,
 }
 
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Circle {
+    pub center: glm::Vec2,
+    pub radius: f32,
+}
+
+/// Axis aligned box spanning `min` to `max`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Aabb {
+    pub min: glm::Vec2,
+    pub max: glm::Vec2,
+}
+
+/// Every point within `radius` of `segment`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Capsule {
+    pub segment: Surface,
+    pub radius: f32,
+}
+
+/// How two overlapping shapes touch. `normal` is a unit vector pointing from the second shape
+/// towards the first, so moving the first shape by `normal * depth` separates them.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Contact {
+    pub normal: glm::Vec2,
+    pub depth: f32,        // how far the shapes overlap along `normal`, never negative
+    pub point: glm::Vec2,  // point on the second shape's boundary
+}
+
+/// Result of a sweep test.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Hit {
+    pub toi: f32,          // time of impact as a fraction of the swept displacement, in [0, 1]
+    pub normal: glm::Vec2, // unit contact normal, pointing away from the surface
+    pub point: glm::Vec2,  // point on the surface that was hit
+}
+
 impl Surface {
-    pub fn find_closest_point(&self, p: &glm::Vec2) -> glm::Vec2 {
-        let AB = self.b - self.a;
-        let AB2 = glm::dot(&AB, &AB);
-        if AB2 == 0.0f32 {
-            return self.a;
+    pub fn new(a: glm::Vec2, b: glm::Vec2) -> Self {
+        Surface { a, b }
+    }
+
+    pub fn length(&self) -> f32 {
+        glm::distance(&self.a, &self.b)
+    }
+
+    /// Unit normal on the left of `a -> b`, or zero for a degenerate segment.
+    pub fn normal(&self) -> glm::Vec2 {
+        let ab = self.b - self.a;
+        glm::Vec2::new(-ab.y, ab.x).try_normalize(f32::EPSILON)
+            .unwrap_or_else(glm::Vec2::zeros)
+    }
+
+    /// Parameter in `[0, 1]` of the point on the segment closest to `p`.
+    pub fn closest_t(&self, p: &glm::Vec2) -> f32 {
+        let ab = self.b - self.a;
+        let ab2 = glm::dot(&ab, &ab);
+        if ab2 == 0.0f32 {
+            return 0.0f32;
         }
         let w = p - self.a;
-        let mut t = AB.dot(&w) / AB2;
-        t = t.clamp(-1.0, 1.0);
+        (ab.dot(&w) / ab2).clamp(0.0, 1.0)
+    }
+
+    pub fn point_at(&self, t: f32) -> glm::Vec2 {
+        self.a + t * (self.b - self.a)
+    }
+
+    pub fn find_closest_point(&self, p: &glm::Vec2) -> glm::Vec2 {
+        self.point_at(self.closest_t(p))
+    }
+
+    pub fn distance_to(&self, p: &glm::Vec2) -> f32 {
+        glm::distance(&self.find_closest_point(p), p)
+    }
+
+    /// Closest pair of points between this segment and `other`, this segment's point first.
+    pub fn closest_points(&self, other: &Surface) -> (glm::Vec2, glm::Vec2) {
+        let d1 = self.b - self.a;
+        let d2 = other.b - other.a;
+        let r = self.a - other.a;
+        let a = glm::dot(&d1, &d1);
+        let e = glm::dot(&d2, &d2);
+        let f = glm::dot(&d2, &r);
+
+        if a <= f32::EPSILON && e <= f32::EPSILON {
+            return (self.a, other.a);
+        }
+
+        let (s, t);
+        if a <= f32::EPSILON {
+            s = 0.0f32;
+            t = (f / e).clamp(0.0, 1.0);
+        } else {
+            let c = glm::dot(&d1, &r);
+            if e <= f32::EPSILON {
+                t = 0.0f32;
+                s = (-c / a).clamp(0.0, 1.0);
+            } else {
+                let b = glm::dot(&d1, &d2);
+                let denom = a * e - b * b;
+                let s0 = if denom != 0.0f32 {
+                    ((b * f - c * e) / denom).clamp(0.0, 1.0)
+                } else {
+                    0.0f32 // parallel, any s will do
+                };
+                let t0 = (b * s0 + f) / e;
+                if t0 < 0.0f32 {
+                    t = 0.0f32;
+                    s = (-c / a).clamp(0.0, 1.0);
+                } else if t0 > 1.0f32 {
+                    t = 1.0f32;
+                    s = ((b - c) / a).clamp(0.0, 1.0);
+                } else {
+                    t = t0;
+                    s = s0;
+                }
+            }
+        }
+
+        (self.point_at(s), other.point_at(t))
+    }
+}
+
+impl Circle {
+    pub fn new(center: glm::Vec2, radius: f32) -> Self {
+        Circle { center, radius }
+    }
+
+    pub fn contains(&self, p: &glm::Vec2) -> bool {
+        glm::distance2(&self.center, p) <= self.radius * self.radius
+    }
+
+    /// Closest point in the circle to `p`, which is `p` itself if it's inside.
+    pub fn closest_point(&self, p: &glm::Vec2) -> glm::Vec2 {
+        let offset = p - self.center;
+        let distance = offset.norm();
+        if distance <= self.radius {
+            *p
+        } else {
+            self.center + offset * (self.radius / distance)
+        }
+    }
+
+    pub fn bounds(&self) -> Aabb {
+        Aabb::from_center(self.center, glm::Vec2::new(self.radius, self.radius))
+    }
 
-        // glm::Vec2::new(
-        //     (self.a.x + t*AB.x),
-        //     (self.a.y + t*AB.y),
-        // )
-        return self.a + t * AB;
+    pub fn overlaps_circle(&self, other: &Circle) -> bool {
+        let r = self.radius + other.radius;
+        glm::distance2(&self.center, &other.center) <= r * r
+    }
+
+    pub fn overlaps_aabb(&self, aabb: &Aabb) -> bool {
+        glm::distance2(&aabb.closest_point(&self.center), &self.center) <= self.radius * self.radius
+    }
+
+    pub fn overlaps_capsule(&self, capsule: &Capsule) -> bool {
+        let r = self.radius + capsule.radius;
+        let closest = capsule.segment.find_closest_point(&self.center);
+        glm::distance2(&closest, &self.center) <= r * r
+    }
+}
+
+impl Aabb {
+    pub fn new(min: glm::Vec2, max: glm::Vec2) -> Self {
+        Aabb { min, max }
+    }
+
+    pub fn from_center(center: glm::Vec2, half_extents: glm::Vec2) -> Self {
+        Aabb {
+            min: center - half_extents,
+            max: center + half_extents,
+        }
+    }
+
+    pub fn center(&self) -> glm::Vec2 {
+        (self.min + self.max) * 0.5f32
+    }
+
+    pub fn half_extents(&self) -> glm::Vec2 {
+        (self.max - self.min) * 0.5f32
+    }
+
+    pub fn contains(&self, p: &glm::Vec2) -> bool {
+        p.x >= self.min.x && p.x <= self.max.x && p.y >= self.min.y && p.y <= self.max.y
+    }
+
+    /// Closest point in the box to `p`, which is `p` itself if it's inside.
+    pub fn closest_point(&self, p: &glm::Vec2) -> glm::Vec2 {
+        glm::Vec2::new(
+            p.x.clamp(self.min.x, self.max.x),
+            p.y.clamp(self.min.y, self.max.y),
+        )
+    }
+
+    pub fn overlaps(&self, other: &Aabb) -> bool {
+        self.min.x <= other.max.x && self.max.x >= other.min.x &&
+            self.min.y <= other.max.y && self.max.y >= other.min.y
+    }
+
+    /// The box pulled in by `amount` on every side (or pushed out for a negative amount). Handy
+    /// for keeping something of a given half size inside this box: clamp its centre to
+    /// `shrink(half_extents)`.
+    pub fn shrink(&self, amount: glm::Vec2) -> Aabb {
+        let min = self.min + amount;
+        let max = self.max - amount;
+        // Collapse onto the centre rather than turning inside out.
+        let center = self.center();
+        Aabb {
+            min: glm::Vec2::new(min.x.min(center.x), min.y.min(center.y)),
+            max: glm::Vec2::new(max.x.max(center.x), max.y.max(center.y)),
+        }
+    }
+
+    /// The four edges, wound counter-clockwise so each `Surface::normal` points into the box.
+    pub fn edges(&self) -> [Surface; 4] {
+        let bottom_left = self.min;
+        let bottom_right = glm::Vec2::new(self.max.x, self.min.y);
+        let top_right = self.max;
+        let top_left = glm::Vec2::new(self.min.x, self.max.y);
+        [
+            Surface::new(bottom_left, bottom_right),
+            Surface::new(bottom_right, top_right),
+            Surface::new(top_right, top_left),
+            Surface::new(top_left, bottom_left),
+        ]
     }
 }
 
+impl Capsule {
+    pub fn new(a: glm::Vec2, b: glm::Vec2, radius: f32) -> Self {
+        Capsule {
+            segment: Surface::new(a, b),
+            radius,
+        }
+    }
+
+    pub fn contains(&self, p: &glm::Vec2) -> bool {
+        self.segment.distance_to(p) <= self.radius
+    }
+
+    /// Closest point in the capsule to `p`, which is `p` itself if it's inside.
+    pub fn closest_point(&self, p: &glm::Vec2) -> glm::Vec2 {
+        let core = self.segment.find_closest_point(p);
+        Circle::new(core, self.radius).closest_point(p)
+    }
+
+    pub fn bounds(&self) -> Aabb {
+        let r = glm::Vec2::new(self.radius, self.radius);
+        Aabb {
+            min: glm::min2(&self.segment.a, &self.segment.b) - r,
+            max: glm::max2(&self.segment.a, &self.segment.b) + r,
+        }
+    }
+}
+
+pub fn contact_circle_circle(a: &Circle, b: &Circle) -> Option<Contact> {
+    let offset = a.center - b.center;
+    let distance = offset.norm();
+    let reach = a.radius + b.radius;
+    if distance > reach {
+        return None;
+    }
+
+    // Concentric circles have no preferred direction, so pick one.
+    let normal = offset.try_normalize(f32::EPSILON).unwrap_or_else(|| glm::Vec2::new(0.0, 1.0));
+    Some(Contact {
+        normal,
+        depth: reach - distance,
+        point: b.center + normal * b.radius,
+    })
+}
+
+pub fn contact_circle_aabb(circle: &Circle, aabb: &Aabb) -> Option<Contact> {
+    if !aabb.contains(&circle.center) {
+        let closest = aabb.closest_point(&circle.center);
+        let offset = circle.center - closest;
+        let distance = offset.norm();
+        if distance > circle.radius {
+            return None;
+        }
+        return Some(Contact {
+            normal: offset / distance,
+            depth: circle.radius - distance,
+            point: closest,
+        });
+    }
+
+    // Centre is inside the box: push out through the nearest face.
+    let c = circle.center;
+    let faces = [
+        (c.x - aabb.min.x, glm::Vec2::new(-1.0, 0.0), glm::Vec2::new(aabb.min.x, c.y)),
+        (aabb.max.x - c.x, glm::Vec2::new(1.0, 0.0), glm::Vec2::new(aabb.max.x, c.y)),
+        (c.y - aabb.min.y, glm::Vec2::new(0.0, -1.0), glm::Vec2::new(c.x, aabb.min.y)),
+        (aabb.max.y - c.y, glm::Vec2::new(0.0, 1.0), glm::Vec2::new(c.x, aabb.max.y)),
+    ];
+    let (distance, normal, point) = faces.into_iter()
+        .fold(faces[0], |best, face| if face.0 < best.0 { face } else { best });
+
+    Some(Contact {
+        normal,
+        depth: circle.radius + distance,
+        point,
+    })
+}
+
+pub fn contact_circle_capsule(circle: &Circle, capsule: &Capsule) -> Option<Contact> {
+    let core = capsule.segment.find_closest_point(&circle.center);
+    contact_circle_circle(circle, &Circle::new(core, capsule.radius))
+}
+
+/// Contact between the closest points of the two cores. If the cores actually cross there is no
+/// single closest pair and the normal is arbitrary.
+pub fn contact_capsule_capsule(a: &Capsule, b: &Capsule) -> Option<Contact> {
+    let (on_a, on_b) = a.segment.closest_points(&b.segment);
+    contact_circle_circle(&Circle::new(on_a, a.radius), &Circle::new(on_b, b.radius))
+}
+
+pub fn contact_aabb_aabb(a: &Aabb, b: &Aabb) -> Option<Contact> {
+    let overlap_x = a.max.x.min(b.max.x) - a.min.x.max(b.min.x);
+    let overlap_y = a.max.y.min(b.max.y) - a.min.y.max(b.min.y);
+    if overlap_x < 0.0f32 || overlap_y < 0.0f32 {
+        return None;
+    }
+
+    // Shortest way out along each axis; for a box nested inside the other this is further than
+    // the overlap itself.
+    let axis = |a_min: f32, a_max: f32, b_min: f32, b_max: f32| {
+        let up = b_max - a_min;
+        let down = a_max - b_min;
+        if up < down { (1.0f32, up) } else { (-1.0f32, down) }
+    };
+    let (sign_x, depth_x) = axis(a.min.x, a.max.x, b.min.x, b.max.x);
+    let (sign_y, depth_y) = axis(a.min.y, a.max.y, b.min.y, b.max.y);
+    let (normal, depth) = if depth_x < depth_y {
+        (glm::Vec2::new(sign_x, 0.0), depth_x)
+    } else {
+        (glm::Vec2::new(0.0, sign_y), depth_y)
+    };
+
+    let overlap = Aabb::new(glm::max2(&a.min, &b.min), glm::min2(&a.max, &b.max));
+    Some(Contact {
+        normal,
+        depth,
+        point: overlap.center(),
+    })
+}
+
+/// Sweeps a circle of `radius` from `center` along `displacement` and returns the first contact
+/// with `surface`, if there is one. The segment is treated as two sided and its end points are
+/// rounded, so a circle clipping the end of a segment hits the corner rather than passing it.
+/// Circles moving away from (or parallel to) the surface never hit it.
+pub fn sweep_circle_segment(center: &glm::Vec2, radius: f32, displacement: &glm::Vec2, surface: &Surface) -> Option<Hit> {
+    let mut best: Option<Hit> = None;
+
+    let ab = surface.b - surface.a;
+    let length2 = glm::dot(&ab, &ab);
+    if length2 > 0.0f32 {
+        // Face of the segment, on whichever side the circle starts.
+        let mut normal = surface.normal();
+        let mut distance = glm::dot(&(center - surface.a), &normal);
+        if distance < 0.0f32 {
+            normal = -normal;
+            distance = -distance;
+        }
+
+        let approach = glm::dot(displacement, &normal);
+        if approach < 0.0f32 {
+            let toi = ((distance - radius) / -approach).max(0.0f32);
+            if toi <= 1.0f32 {
+                let contact = center + displacement * toi - normal * radius;
+                let t = glm::dot(&(contact - surface.a), &ab) / length2;
+                if (0.0f32..=1.0f32).contains(&t) {
+                    best = Some(Hit { toi, normal, point: surface.point_at(t) });
+                }
+            }
+        }
+    }
+
+    for end in [surface.a, surface.b] {
+        if let Some(hit) = sweep_circle_point(center, radius, displacement, &end)
+            && best.is_none_or(|b| hit.toi < b.toi) {
+            best = Some(hit);
+        }
+    }
+
+    best
+}
+
+// Sweeps a circle against a single point, the rounded end of a segment.
+fn sweep_circle_point(center: &glm::Vec2, radius: f32, displacement: &glm::Vec2, point: &glm::Vec2) -> Option<Hit> {
+    let offset = center - point;
+    let a = glm::dot(displacement, displacement);
+    let b = glm::dot(&offset, displacement);
+    let c = glm::dot(&offset, &offset) - radius * radius;
+
+    if b >= 0.0f32 || a == 0.0f32 {
+        return None; // not moving towards the point
+    }
+
+    let toi = if c <= 0.0f32 {
+        0.0f32 // already touching
+    } else {
+        let discriminant = b * b - a * c;
+        if discriminant < 0.0f32 {
+            return None;
+        }
+        (-b - discriminant.sqrt()) / a
+    };
+
+    if toi > 1.0f32 {
+        return None;
+    }
+
+    let at = center + displacement * toi;
+    let normal = (at - point).try_normalize(f32::EPSILON)
+        .unwrap_or_else(|| -displacement.normalize());
+    Some(Hit { toi, normal, point: *point })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::rng::Rng;
+
+    const CASES: usize = 2_000;
+    const EPS: f32 = 1e-4;
+
+    fn wall() -> Surface {
+        Surface {
+            a: glm::Vec2::new(1.0, -1.0),
+            b: glm::Vec2::new(1.0, 1.0),
+        }
+    }
+
+    fn vec2(rng: &mut Rng) -> glm::Vec2 {
+        glm::Vec2::new(rng.range(-2.0, 2.0), rng.range(-2.0, 2.0))
+    }
+
+    fn segment(rng: &mut Rng) -> Surface {
+        Surface::new(vec2(rng), vec2(rng))
+    }
+
+    fn aabb(rng: &mut Rng) -> Aabb {
+        let half = glm::Vec2::new(rng.range(0.01, 1.0), rng.range(0.01, 1.0));
+        Aabb::from_center(vec2(rng), half)
+    }
+
+    fn circle(rng: &mut Rng) -> Circle {
+        Circle::new(vec2(rng), rng.range(0.01, 1.0))
+    }
+
+    fn capsule(rng: &mut Rng) -> Capsule {
+        Capsule {
+            segment: segment(rng),
+            radius: rng.range(0.01, 1.0),
+        }
+    }
+
+    #[test]
+    fn closest_point_stays_on_segment() {
+        // The old clamp to [-1, 1] handed back points off the end of the segment.
+        let surface = Surface::new(glm::Vec2::new(0.0, 0.0), glm::Vec2::new(1.0, 0.0));
+        assert_eq!(glm::Vec2::new(0.0, 0.0), surface.find_closest_point(&glm::Vec2::new(-5.0, 1.0)));
+        assert_eq!(glm::Vec2::new(1.0, 0.0), surface.find_closest_point(&glm::Vec2::new(5.0, 1.0)));
+    }
+
+    #[test]
+    fn prop_segment_closest_point_is_nearest() {
+        let mut rng = Rng::new(1);
+        for _ in 0..CASES {
+            let s = segment(&mut rng);
+            let p = vec2(&mut rng);
+            let closest = s.find_closest_point(&p);
+            assert!(s.distance_to(&closest) < EPS, "{:?} not on {:?}", closest, s);
+            for _ in 0..8 {
+                let other = s.point_at(rng.next_f32());
+                assert!(glm::distance(&closest, &p) <= glm::distance(&other, &p) + EPS);
+            }
+        }
+    }
+
+    #[test]
+    fn prop_segment_pair_closest_points_are_nearest() {
+        let mut rng = Rng::new(2);
+        for _ in 0..CASES {
+            let (s1, s2) = (segment(&mut rng), segment(&mut rng));
+            let (p, q) = s1.closest_points(&s2);
+            assert!(s1.distance_to(&p) < EPS && s2.distance_to(&q) < EPS);
+            for _ in 0..8 {
+                let a = s1.point_at(rng.next_f32());
+                let b = s2.point_at(rng.next_f32());
+                assert!(glm::distance(&p, &q) <= glm::distance(&a, &b) + EPS);
+            }
+        }
+    }
+
+    #[test]
+    fn prop_closest_points_lie_inside_shapes() {
+        let mut rng = Rng::new(3);
+        for _ in 0..CASES {
+            let p = vec2(&mut rng);
+            let (b, c, k) = (aabb(&mut rng), circle(&mut rng), capsule(&mut rng));
+            assert!(b.contains(&b.closest_point(&p)));
+            assert!(Circle::new(c.center, c.radius + EPS).contains(&c.closest_point(&p)));
+            assert!(k.segment.distance_to(&k.closest_point(&p)) <= k.radius + EPS);
+            if b.contains(&p) {
+                assert_eq!(p, b.closest_point(&p));
+            }
+        }
+    }
+
+    #[test]
+    fn prop_overlap_agrees_with_contact() {
+        let mut rng = Rng::new(4);
+        for _ in 0..CASES {
+            let (c, b, k) = (circle(&mut rng), aabb(&mut rng), capsule(&mut rng));
+            let other = circle(&mut rng);
+            assert_eq!(c.overlaps_aabb(&b), contact_circle_aabb(&c, &b).is_some());
+            assert_eq!(c.overlaps_circle(&other), contact_circle_circle(&c, &other).is_some());
+            assert_eq!(c.overlaps_capsule(&k), contact_circle_capsule(&c, &k).is_some());
+            let b2 = aabb(&mut rng);
+            assert_eq!(b.overlaps(&b2), b2.overlaps(&b));
+            assert_eq!(b.overlaps(&b2), contact_aabb_aabb(&b, &b2).is_some());
+        }
+    }
+
+    // Every manifold should have a unit normal and a depth that, applied, just separates the pair.
+    fn check_manifold(contact: Option<Contact>, separated: impl Fn(glm::Vec2) -> bool) {
+        if let Some(contact) = contact {
+            assert!(contact.depth >= 0.0);
+            assert!((contact.normal.norm() - 1.0).abs() < EPS);
+            assert!(separated(contact.normal * (contact.depth + 1e-3)), "{:?}", contact);
+        }
+    }
+
+    #[test]
+    fn prop_contacts_separate_shapes() {
+        let mut rng = Rng::new(5);
+        for _ in 0..CASES {
+            let (c, other, b, k, k2, b2) = (
+                circle(&mut rng), circle(&mut rng), aabb(&mut rng),
+                capsule(&mut rng), capsule(&mut rng), aabb(&mut rng),
+            );
+            let moved = |circle: Circle, by: glm::Vec2| Circle::new(circle.center + by, circle.radius);
+
+            check_manifold(contact_circle_circle(&c, &other),
+                |by| !moved(c, by).overlaps_circle(&other));
+            check_manifold(contact_circle_aabb(&c, &b),
+                |by| !moved(c, by).overlaps_aabb(&b));
+            check_manifold(contact_circle_capsule(&c, &k),
+                |by| !moved(c, by).overlaps_capsule(&k));
+            check_manifold(contact_aabb_aabb(&b2, &b),
+                |by| !Aabb::new(b2.min + by, b2.max + by).overlaps(&b));
+            // Crossing cores have no single closest pair, so there is no meaningful normal there.
+            let (p, q) = k2.segment.closest_points(&k.segment);
+            if glm::distance(&p, &q) < EPS {
+                continue;
+            }
+            check_manifold(contact_capsule_capsule(&k2, &k), |by| {
+                let shifted = Capsule::new(k2.segment.a + by, k2.segment.b + by, k2.radius);
+                contact_capsule_capsule(&shifted, &k).is_none()
+            });
+        }
+    }
+
+    #[test]
+    fn shrink_keeps_half_sizes_inside() {
+        let court = Aabb::new(glm::Vec2::new(-1.0, -1.0), glm::Vec2::new(1.0, 1.0));
+        let inner = court.shrink(glm::Vec2::new(0.1, 0.2));
+        assert_eq!(glm::Vec2::new(0.9, 0.8), inner.closest_point(&glm::Vec2::new(3.0, 3.0)));
+        let collapsed = court.shrink(glm::Vec2::new(2.0, 0.0));
+        assert_eq!(0.0, collapsed.closest_point(&glm::Vec2::new(3.0, 0.0)).x);
+    }
+
+    #[test]
+    fn sweep_hits_face() {
+        let hit = sweep_circle_segment(
+            &glm::Vec2::new(0.0, 0.0), 0.1, &glm::Vec2::new(2.0, 0.0), &wall()
+        ).expect("should hit the wall");
+        assert!((hit.toi - 0.45).abs() < 1e-5);
+        assert_eq!(glm::Vec2::new(-1.0, 0.0), hit.normal);
+        assert!((hit.point - glm::Vec2::new(1.0, 0.0)).norm() < 1e-5);
+    }
+
+    #[test]
+    fn sweep_tunneling_displacement() {
+        // Far longer than the circle is wide: the end position is way past the wall.
+        let hit = sweep_circle_segment(
+            &glm::Vec2::new(0.0, 0.0), 0.01, &glm::Vec2::new(100.0, 0.0), &wall()
+        );
+        assert!(hit.is_some());
+    }
+
+    #[test]
+    fn sweep_ignores_receding_and_missing() {
+        let surface = wall();
+        let center = glm::Vec2::new(0.0, 0.0);
+        assert!(sweep_circle_segment(&center, 0.1, &glm::Vec2::new(-2.0, 0.0), &surface).is_none());
+        assert!(sweep_circle_segment(&center, 0.1, &glm::Vec2::new(0.5, 0.0), &surface).is_none());
+        assert!(sweep_circle_segment(&center, 0.1, &glm::Vec2::new(0.0, 2.0), &surface).is_none());
+        // Passes above the end of the segment.
+        assert!(sweep_circle_segment(&center, 0.1, &glm::Vec2::new(2.0, 2.5), &surface).is_none());
+    }
+
+    #[test]
+    fn sweep_hits_rounded_end() {
+        let hit = sweep_circle_segment(
+            &glm::Vec2::new(0.0, 1.05), 0.1, &glm::Vec2::new(2.0, 0.0), &wall()
+        ).expect("should clip the corner");
+        assert_eq!(glm::Vec2::new(1.0, 1.0), hit.point);
+        assert!(hit.normal.x < 0.0 && hit.normal.y > 0.0);
+    }
+
+    #[test]
+    fn prop_sweep_stops_at_contact() {
+        let mut rng = Rng::new(6);
+        for _ in 0..CASES {
+            let s = segment(&mut rng);
+            let c = circle(&mut rng);
+            if c.overlaps_capsule(&Capsule { segment: s, radius: 0.0 }) {
+                continue;
+            }
+            let displacement = vec2(&mut rng) * 2.0;
+            if let Some(hit) = sweep_circle_segment(&c.center, c.radius, &displacement, &s) {
+                let at = c.center + displacement * hit.toi;
+                assert!((s.distance_to(&at) - c.radius).abs() < 1e-3);
+                assert!(glm::dot(&hit.normal, &displacement) < 0.0);
+            }
+        }
+    }
+}
@@ -3,6 +3,7 @@ use crate::core::input::{InputState, KeyKind};
 use nalgebra_glm as glm;
 
 
+#[derive(Clone, Debug)]
 pub struct OrthographicCamera {
     position: glm::Vec3,
     rotation: f32,
@@ -77,6 +78,20 @@ impl OrthographicCamera {
     pub fn get_position(&self) -> &glm::Vec3 {
         &self.position
     }
+
+    /// Where a point on the window lands in the world. `screen` is in pixels from the top left,
+    /// as winit reports the cursor, and `viewport` is the window size in pixels.
+    pub fn screen_to_world(&self, screen: &glm::Vec2, viewport: &glm::Vec2) -> glm::Vec2 {
+        // Pixels go down the window, clip space goes up.
+        let ndc = glm::Vec4::new(
+            screen.x / viewport.x * 2.0f32 - 1.0f32,
+            1.0f32 - screen.y / viewport.y * 2.0f32,
+            0.0f32,
+            1.0f32,
+        );
+        let world = glm::inverse(&self.view_projection) * ndc;
+        glm::Vec2::new(world.x, world.y)
+    }
 }
 
 
@@ -108,23 +123,23 @@ impl OrthographicCameraController {
     pub fn update(&mut self, delta: f32, input: &InputState) {
         let mut new_position = self.camera.position;
 
-        if input.is_key_pressed(&KeyKind::ArrowLeft) {
+        if input.is_key_pressed(KeyKind::ArrowLeft) {
             new_position.x -= self.camera.rotation.to_radians().cos() * 
                 self.camera_translation_speed * delta;
             new_position.y -= self.camera.rotation.to_radians().sin() * 
                 self.camera_translation_speed * delta;
-        } else if input.is_key_pressed(&KeyKind::ArrowRight) {
+        } else if input.is_key_pressed(KeyKind::ArrowRight) {
             new_position.x += self.camera.rotation.to_radians().cos() * 
                 self.camera_translation_speed * delta;
             new_position.y += self.camera.rotation.to_radians().sin() * 
                 self.camera_translation_speed * delta;
-        } else if input.is_key_pressed(&KeyKind::ArrowUp) {
+        } else if input.is_key_pressed(KeyKind::ArrowUp) {
             new_position.x += 
                 -(self.camera.rotation.to_radians().sin()) * 
                 self.camera_translation_speed * delta;
             new_position.y += self.camera.rotation.to_radians().cos() * 
                 self.camera_translation_speed * delta;
-        } else if input.is_key_pressed(&KeyKind::ArrowDown) {
+        } else if input.is_key_pressed(KeyKind::ArrowDown) {
             new_position.x -= -(self.camera.rotation.to_radians().sin()) * 
                 self.camera_translation_speed * delta;
             new_position.y -= self.camera.rotation.to_radians().cos() * 
@@ -146,3 +161,26 @@ impl OrthographicCameraController {
     }
 }
 
+
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn screen_to_world_undoes_the_projection() {
+        let aspect_ratio = 2.0f32;
+        let mut camera = OrthographicCamera::new(-aspect_ratio, aspect_ratio, -1.0f32, 1.0f32);
+        let viewport = glm::Vec2::new(800.0f32, 400.0f32);
+
+        let top_left = camera.screen_to_world(&glm::Vec2::new(0.0f32, 0.0f32), &viewport);
+        assert!(glm::distance(&glm::Vec2::new(-2.0f32, 1.0f32), &top_left) < 1e-6);
+        let quarter = camera.screen_to_world(&glm::Vec2::new(600.0f32, 300.0f32), &viewport);
+        assert!(glm::distance(&glm::Vec2::new(1.0f32, -0.5f32), &quarter) < 1e-6);
+
+        camera.set_position(glm::Vec3::new(1.0f32, 0.0f32, 0.0f32));
+        let center = camera.screen_to_world(&glm::Vec2::new(400.0f32, 200.0f32), &viewport);
+        assert!(glm::distance(&glm::Vec2::new(1.0f32, 0.0f32), &center) < 1e-6);
+    }
+}
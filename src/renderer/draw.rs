@@ -36,6 +36,19 @@ impl OpenGLRendererAPI {
     pub fn get_error(&self) -> u32 {
         unsafe { self.0.get_error() }
     }
+
+    /// How many textures a fragment shader can sample from at once.
+    pub fn max_texture_slots(&self) -> usize {
+        unsafe { self.0.get_parameter_i32(glow::MAX_TEXTURE_IMAGE_UNITS) as usize }
+    }
+
+    /// Blends whatever is drawn over what's already there by its alpha.
+    pub fn enable_blending(&self) {
+        unsafe {
+            self.0.enable(glow::BLEND);
+            self.0.blend_func(glow::SRC_ALPHA, glow::ONE_MINUS_SRC_ALPHA);
+        }
+    }
 }
 
 
@@ -62,7 +75,7 @@ impl RendererApi for OpenGLRendererAPI {
     fn draw_lines(&self, vertex_array: &impl VertexArray, vertex_count: usize) {
         vertex_array.bind();
         unsafe {
-            self.0.draw_arrays(glow::TRIANGLES, 0, vertex_count as i32);
+            self.0.draw_arrays(glow::LINES, 0, vertex_count as i32);
         }
     }
 
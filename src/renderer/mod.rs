@@ -41,20 +41,29 @@ struct QuadVertex {
 
 
 
-impl QuadVertex {
-    fn new() -> Self {
-        Self {
-            position: glm::Vec3::zeros(),
-            color: glm::Vec4::zeros(),
-            tex_coord: glm::Vec2::zeros(),
-            tex_index: 0.0,
-            tiling_factor: 1.0,
-            entity_id: -1,
-        }
-    }
+#[derive(Copy, Clone, Debug)]
+#[repr(C)]
+struct CircleVertex {
+    world_position: glm::Vec3,
+    local_position: glm::Vec3, // -1 to 1 across the circle's quad
+    color: glm::Vec4,
+    thickness: f32,
+    fade: f32,
+    entity_id: i32,
+}
+
+
+
+#[derive(Copy, Clone, Debug)]
+#[repr(C)]
+struct LineVertex {
+    position: glm::Vec3,
+    color: glm::Vec4,
+    entity_id: i32,
 }
 
 
+
 #[derive(Debug)]
 #[repr(C)]
 struct CameraData {
@@ -74,10 +83,32 @@ impl CameraData {
 
 
 const QUAD_VERTEX_COUNT: usize = 4;
-const MAX_QUADS: usize = 20;
-const MAX_VERTICES: usize = MAX_QUADS * QUAD_VERTEX_COUNT;
-const MAX_INDICES: usize = MAX_QUADS * 6;
-const MAX_TEXTURE_SLOTS: usize = 32;
+const QUAD_INDEX_COUNT: usize = 6;
+
+
+/// How much `Renderer2D` draws per batch. A batch is flushed as one draw call once it's out of
+/// room for quads or texture slots, so bigger batches mean fewer draw calls and more memory.
+#[derive(Clone, Copy, Debug)]
+pub struct Renderer2DConfig {
+    pub max_quads: usize,
+    /// Capped at what the driver supports.
+    pub max_texture_slots: usize,
+}
+
+impl Default for Renderer2DConfig {
+    fn default() -> Self {
+        Self {
+            max_quads: 10_000,
+            max_texture_slots: 32,
+        }
+    }
+}
+
+impl Renderer2DConfig {
+    fn max_vertices(&self) -> usize {
+        self.max_quads * QUAD_VERTEX_COUNT
+    }
+}
 
 const TEXTURE_COORDS: [glm::Vec2; 4] = [
     glm::Vec2::new(0.0, 0.0),
@@ -94,36 +125,41 @@ struct Renderer2DData {
     // quad_vertex_array: Box<dyn VertexArray<Item = GLVertexBuffer, Item2 = GLIndexBuffer>>,
     // quad_shader: i32, // TODO: shader implementations.
 
-    // circle_vertex_array: Box<dyn VertexArray<Item = GLVertexBuffer>>,
-    // circle_vertex_buffer: Box<dyn VertexBuffer>,
-    // // circle_shader: i32, // TODO: SHADER
-    //
-    // line_vertex_array: Box<dyn VertexArray<Item = GLVertexBuffer>>,
-    // line_vertex_buffer: Box<dyn VertexBuffer>,
-    // // line_shader: i32,
-    //
     // text_vertex_array: Box<dyn VertexArray<Item = GLVertexBuffer>>,
     // text_vertex_buffer: Box<dyn VertexBuffer>,
     // // text_shader: i32,
 
     quad_index_count: u32,
-    quad_vertex_buffer_base: Box<[QuadVertex; MAX_VERTICES]>,
-    quad_vertex_buffer_idx: usize, // index position of current quad_vertex_buffer_base
+    quad_vertex_buffer_base: Vec<QuadVertex>, // the current batch, at most `max_vertices` long
     quad_vertex_positions: [glm::Vec4; 4], 
     quad_shader: GLShader,
 
-    texture_slots: Vec<GLTexture>,
-    texture_slot_idx: usize, // TODO: 0 = white_texture???
+    circle_vertex_array: Box<GLVertexArray>,
+    circle_vertex_buffer: Box<GLVertexBuffer>,
+    circle_index_count: u32,
+    circle_vertex_buffer_base: Vec<CircleVertex>, // the current batch, at most `max_vertices` long
+    circle_shader: GLShader,
+
+    line_vertex_array: Box<GLVertexArray>,
+    line_vertex_buffer: Box<GLVertexBuffer>,
+    line_vertex_buffer_base: Vec<LineVertex>, // the current batch, two per line
+    line_shader: GLShader,
+    line_width: f32,
+
+    texture_slots: Vec<Rc<GLTexture>>, // the current batch's textures, a white one in slot 0
 
     camera_uniform_buffer: Box<GLUniformBuffer>,
     camera_data: CameraData,
 }
 
 
-#[derive(Debug)]
-struct RenderStats {
+/// What was drawn since the last `Renderer2D::begin_scene`.
+#[derive(Debug, Default)]
+pub struct RenderStats {
     draw_calls: usize,
     quad_count: usize,
+    circle_count: usize,
+    line_count: usize,
 }
 
 
@@ -132,12 +168,19 @@ pub struct Renderer2D {
     data: Box<Renderer2DData>,
     backend: RendererBackend,
     stats: RenderStats,
+    config: Renderer2DConfig,
+    clear_color: glm::Vec4, // what `begin_scene` clears to
 }
 
 
 
 impl Renderer2D {
     pub fn new(gl: glow::Context, width: i32, height: i32) -> Self {
+        Self::with_config(gl, width, height, Renderer2DConfig::default())
+    }
+
+    pub fn with_config(gl: glow::Context, width: i32, height: i32, mut config: Renderer2DConfig) -> Self {
+        assert!(config.max_quads > 0, "Renderer2D needs room for at least one quad");
 
         let quad_layout = BufferLayoutBuilder::new()
             .element(BufferElement::new(ShaderDataType::Float3, "a_Position", false))
@@ -149,34 +192,20 @@ impl Renderer2D {
             .build();
         println!("{:?}", quad_layout);
         let gl_rc = Rc::new(gl);
+        let ogl = OpenGLRendererAPI::new(gl_rc.clone());
+        // Slot 0 is always the white texture, so there has to be room for one more.
+        config.max_texture_slots = config.max_texture_slots.min(ogl.max_texture_slots()).max(2);
+
         let mut quad_buffer = GLVertexBuffer::new(
             gl_rc.clone(), 
             quad_layout,
-            (std::mem::size_of::<QuadVertex>() * MAX_VERTICES) as i32,
+            vertex_buffer_size::<QuadVertex>(&config),
         );
         let mut quad_vertex_array = GLVertexArray::new(gl_rc.clone());
         quad_vertex_array.add_vertex_buffer(&mut quad_buffer);
 
 
-        // I didn't come up with this index array,
-        // however, when using an index buffer the repeated
-        // vertices aren't stored separately, so this is
-        // essentially telling the GPU where to find the vertex
-        // data in the vertex buffer. See 'quad_vertices' below
-        // to see the vertex for each corresponding index
-        // there are 4 different vertices
-        let mut quad_indices: [u32; MAX_INDICES] = [0u32; MAX_INDICES];
-        let mut offset: u32 = 0;
-        for i in (0..MAX_INDICES).step_by(6) {
-            quad_indices[i + 0] = offset + 0;
-            quad_indices[i + 1] = offset + 1;
-            quad_indices[i + 2] = offset + 2;
-            quad_indices[i + 3] = offset + 2;
-            quad_indices[i + 4] = offset + 3;
-            quad_indices[i + 5] = offset + 0;
-            offset += 4;
-        }
-
+        let quad_indices = quad_indices(config.max_quads);
         let quad_index_buffer = GLIndexBuffer::new(gl_rc.clone(), &quad_indices);
         quad_vertex_array.set_index_buffer(quad_index_buffer);
 
@@ -187,7 +216,50 @@ impl Renderer2D {
             glm::Vec4::new(-0.5, 0.5, 0.0, 1.0),
         ];
 
-        let quad_shader = GLShader::new(gl_rc.clone(), "quad_shader", VERTEX_SRC, FRAGMENT_SRC);
+        let quad_shader = GLShader::new(
+            gl_rc.clone(),
+            "quad_shader",
+            VERTEX_SRC,
+            &quad_fragment_src(config.max_texture_slots),
+        );
+
+        let circle_layout = BufferLayoutBuilder::new()
+            .element(BufferElement::new(ShaderDataType::Float3, "a_WorldPosition", false))
+            .element(BufferElement::new(ShaderDataType::Float3, "a_LocalPosition", false))
+            .element(BufferElement::new(ShaderDataType::Float4, "a_Color", false))
+            .element(BufferElement::new(ShaderDataType::Float, "a_Thickness", false))
+            .element(BufferElement::new(ShaderDataType::Float, "a_Fade", false))
+            .element(BufferElement::new(ShaderDataType::Int, "a_EntityId", false))
+            .build();
+        let mut circle_buffer = GLVertexBuffer::new(
+            gl_rc.clone(),
+            circle_layout,
+            vertex_buffer_size::<CircleVertex>(&config),
+        );
+        let mut circle_vertex_array = GLVertexArray::new(gl_rc.clone());
+        circle_vertex_array.add_vertex_buffer(&mut circle_buffer);
+        // Circles are drawn on quads, so they're indexed the same way.
+        circle_vertex_array.set_index_buffer(GLIndexBuffer::new(gl_rc.clone(), &quad_indices));
+
+        let circle_shader = GLShader::new(gl_rc.clone(), "circle_shader", CIRCLE_VERTEX_SRC, CIRCLE_FRAGMENT_SRC);
+
+        let line_layout = BufferLayoutBuilder::new()
+            .element(BufferElement::new(ShaderDataType::Float3, "a_Position", false))
+            .element(BufferElement::new(ShaderDataType::Float4, "a_Color", false))
+            .element(BufferElement::new(ShaderDataType::Int, "a_EntityId", false))
+            .build();
+        let mut line_buffer = GLVertexBuffer::new(
+            gl_rc.clone(),
+            line_layout,
+            vertex_buffer_size::<LineVertex>(&config),
+        );
+        let mut line_vertex_array = GLVertexArray::new(gl_rc.clone());
+        line_vertex_array.add_vertex_buffer(&mut line_buffer);
+
+        let line_shader = GLShader::new(gl_rc.clone(), "line_shader", LINE_VERTEX_SRC, LINE_FRAGMENT_SRC);
+
+        let mut texture_slots = Vec::with_capacity(config.max_texture_slots);
+        texture_slots.push(Rc::new(GLTexture::white(gl_rc.clone())));
 
         let camera_uniform_buffer = GLUniformBuffer::new(
             gl_rc.clone(), 
@@ -201,33 +273,56 @@ impl Renderer2D {
             quad_vertex_buffer: Box::new(quad_buffer),
             quad_index_count: 0,
             quad_vertex_positions: quad_vertices,
-            quad_vertex_buffer_idx: 0,
-            quad_vertex_buffer_base: Box::new([QuadVertex::new(); MAX_VERTICES]),
+            quad_vertex_buffer_base: Vec::with_capacity(config.max_vertices()),
             quad_shader,
-            texture_slots: Vec::with_capacity(MAX_TEXTURE_SLOTS),
-            texture_slot_idx: 0,
+            circle_vertex_array: Box::new(circle_vertex_array),
+            circle_vertex_buffer: Box::new(circle_buffer),
+            circle_index_count: 0,
+            circle_vertex_buffer_base: Vec::with_capacity(config.max_vertices()),
+            circle_shader,
+            line_vertex_array: Box::new(line_vertex_array),
+            line_vertex_buffer: Box::new(line_buffer),
+            line_vertex_buffer_base: Vec::with_capacity(config.max_vertices()),
+            line_shader,
+            line_width: 1.0,
+            texture_slots,
             camera_data: CameraData { view_projection: glm::Mat4::identity() },
             camera_uniform_buffer: Box::new(camera_uniform_buffer),
         });
 
-        let ogl = OpenGLRendererAPI::new(gl_rc);
         ogl.set_viewport(0, 0, width, height);
+        ogl.enable_blending();
         ogl.set_clear_color(&glm::Vec4::new(0.2, 0.5, 0.2, 1.0));
         let backend = RendererBackend::OpenGL(ogl);
 
-        let stats = RenderStats::new();
         Self {
             data,
             backend,
-            stats,
+            stats: RenderStats::default(),
+            config,
+            clear_color: glm::Vec4::new(0.2, 0.3, 0.5, 1.0),
         }
     }
 
+    pub fn set_clear_color(&mut self, color: glm::Vec4) {
+        self.clear_color = color;
+    }
+
+    pub fn config(&self) -> &Renderer2DConfig {
+        &self.config
+    }
+
+    /// What the scene drawn since the last `begin_scene` cost.
+    pub fn stats(&self) -> &RenderStats {
+        &self.stats
+    }
+
     pub fn print_stats(&self) {
         println!("{}", self.stats);
     }
 
     pub fn begin_scene(&mut self, camera: &OrthographicCamera) {
+        self.stats = RenderStats::default();
         self.clear_color();
         self.set_camera_data(camera);
         self.start_batch();
@@ -248,35 +343,63 @@ impl Renderer2D {
 
     fn start_batch(&mut self) {
         self.data.quad_index_count = 0;
-        self.data.quad_vertex_buffer_idx = 0;
-        self.data.texture_slot_idx = 1;
+        self.data.quad_vertex_buffer_base.clear();
+        self.data.circle_index_count = 0;
+        self.data.circle_vertex_buffer_base.clear();
+        self.data.line_vertex_buffer_base.clear();
+        self.data.texture_slots.truncate(1);
     }
 
-    fn next_batch(&mut self) {
+    /// Draws everything submitted so far, so whatever is submitted after it lands on top. Needed
+    /// whenever different kinds of shapes have to layer, see `flush`.
+    pub fn next_batch(&mut self) {
         self.flush();
         self.start_batch();
     }
 
+    // Draws the batch's quads, then its circles, then its lines, whatever order they were
+    // submitted in. Only the order of batches is kept.
     fn flush(&mut self) {
         if self.data.quad_index_count > 0 {
-            let bytes: &[u8] = to_bytes(
-                &self.data.quad_vertex_buffer_base[0..self.data.quad_vertex_buffer_idx]
-            );
+            let bytes: &[u8] = to_bytes(&self.data.quad_vertex_buffer_base);
             self.data.quad_vertex_buffer.set_data(bytes);
 
-            for i in 0..self.data.texture_slots.len() {
-                let tex = &self.data.texture_slots[i];
-                tex.bind(i as u32);
+            for (slot, texture) in self.data.texture_slots.iter().enumerate() {
+                texture.bind(slot as u32);
             }
             
             self.data.quad_shader.bind();
 
-            self.draw_indexed();
+            self.draw_indexed(&self.data.quad_vertex_array, self.data.quad_index_count);
+            self.stats.increment_draw_calls();
+        }
+
+        if self.data.circle_index_count > 0 {
+            let bytes: &[u8] = to_bytes(&self.data.circle_vertex_buffer_base);
+            self.data.circle_vertex_buffer.set_data(bytes);
+
+            self.data.circle_shader.bind();
+
+            self.draw_indexed(&self.data.circle_vertex_array, self.data.circle_index_count);
+            self.stats.increment_draw_calls();
+        }
+
+        if !self.data.line_vertex_buffer_base.is_empty() {
+            let bytes: &[u8] = to_bytes(&self.data.line_vertex_buffer_base);
+            self.data.line_vertex_buffer.set_data(bytes);
+
+            self.data.line_shader.bind();
+
+            match &self.backend {
+                RendererBackend::OpenGL(ogl) => {
+                    ogl.set_line_width(self.data.line_width);
+                    ogl.draw_lines(&*self.data.line_vertex_array, self.data.line_vertex_buffer_base.len());
+                },
+                _ => { panic!("Unsupported renderer backend") },
+            }
             self.stats.increment_draw_calls();
         }
 
-        // TODO: circles
-        // TODO: lines
         // TODO: text
     }
 
@@ -289,64 +412,146 @@ impl Renderer2D {
     }
 
     pub fn draw_quad(&mut self, transform: &glm::Mat4, color: glm::Vec4, entity_id: i32) {
-        if self.data.quad_index_count >= MAX_INDICES as u32 {
+        if self.batch_is_full() {
             self.next_batch();
         }
 
-        for i in (0..QUAD_VERTEX_COUNT) {
-            let vertex: &mut QuadVertex = &mut self.data.quad_vertex_buffer_base[
-                self.data.quad_vertex_buffer_idx
-            ];
-            let position = &(transform * &self.data.quad_vertex_positions[i]);
-            vertex.position = glm::vec4_to_vec3(
-                position
-            );
-            vertex.color = color;
-            vertex.tex_index = 0.0;
-            vertex.tex_coord = TEXTURE_COORDS[i];
-            vertex.tiling_factor = 1.0;
-            vertex.entity_id = entity_id;
-            self.data.quad_vertex_buffer_idx += 1;
+        for (corner, tex_coord) in self.data.quad_vertex_positions.iter().zip(TEXTURE_COORDS) {
+            self.data.quad_vertex_buffer_base.push(QuadVertex {
+                position: glm::vec4_to_vec3(&(transform * corner)),
+                color,
+                tex_coord,
+                tex_index: 0.0,
+                tiling_factor: 1.0,
+                entity_id,
+            });
         }
-        self.data.quad_index_count += 6;
+        self.data.quad_index_count += QUAD_INDEX_COUNT as u32;
         self.stats.increment_quad_count();
     }
 
-    pub fn draw_quad_texture(&mut self, transform: &glm::Mat4, texture: &GLTexture, tint_color: &glm::Vec4) {
-        if self.data.quad_index_count >= MAX_INDICES as u32 {
+    /// Draws `texture` repeated `tiling_factor` times across the quad, multiplied by `tint_color`.
+    pub fn draw_quad_texture(
+        &mut self,
+        transform: &glm::Mat4,
+        texture: &Rc<GLTexture>,
+        tiling_factor: f32,
+        tint_color: &glm::Vec4,
+    ) {
+        if self.batch_is_full() {
             self.next_batch();
         }
-        // TODO: potentially cache the texture?
-        for i in (0..QUAD_VERTEX_COUNT) {
-            let vertex: &mut QuadVertex = &mut self.data.quad_vertex_buffer_base[
-                self.data.quad_vertex_buffer_idx
-            ];
-            let position = &(transform * &self.data.quad_vertex_positions[i]);
-            vertex.position = glm::vec4_to_vec3(
-                position
-            );
-            vertex.color = tint_color.clone();
-            vertex.tex_coord = TEXTURE_COORDS[i];
-            vertex.tex_index = 1.0;
-            vertex.tiling_factor = 1.0;
-            vertex.entity_id = -1;
-
-            self.data.quad_vertex_buffer_idx += 1;
+        let tex_index = self.texture_slot(texture) as f32;
+
+        for (corner, tex_coord) in self.data.quad_vertex_positions.iter().zip(TEXTURE_COORDS) {
+            self.data.quad_vertex_buffer_base.push(QuadVertex {
+                position: glm::vec4_to_vec3(&(transform * corner)),
+                color: *tint_color,
+                tex_coord,
+                tex_index,
+                tiling_factor,
+                entity_id: -1,
+            });
         }
 
-        self.data.quad_index_count += 6;
+        self.data.quad_index_count += QUAD_INDEX_COUNT as u32;
         self.stats.increment_quad_count();
     }
 
-    pub fn load_texture(&self, path: &str) -> Result<GLTexture, Box<dyn Error>> {
-        todo!();
-        // let texture = GLTexture::new(self.gl, path)
+    pub fn load_texture(&self, path: &str) -> Result<Rc<GLTexture>, Box<dyn Error>> {
+        let texture = GLTexture::open(self.data.gl.clone(), path)?;
+        Ok(Rc::new(texture))
     }
 
-    fn draw_indexed(&self) {
+    // The slot `texture` is bound to in this batch, taking the next free one if it isn't yet and
+    // starting a new batch if there are none left.
+    fn texture_slot(&mut self, texture: &Rc<GLTexture>) -> usize {
+        if let Some(slot) = find_texture_slot(&self.data.texture_slots, texture) {
+            return slot;
+        }
+        if self.data.texture_slots.len() >= self.config.max_texture_slots {
+            self.next_batch();
+        }
+        self.data.texture_slots.push(texture.clone());
+        self.data.texture_slots.len() - 1
+    }
+
+    /// Draws a circle filling the unit quad `transform` puts in the world. `thickness` is how far
+    /// in from the edge it's filled, from 0 to 1 where 1 is a disc and less is a ring. `fade` is
+    /// how far its edges blend out, in the same units, so small values down to 0 give sharp edges.
+    pub fn draw_circle(&mut self, transform: &glm::Mat4, color: glm::Vec4, thickness: f32, fade: f32, entity_id: i32) {
+        if self.circle_batch_is_full() {
+            self.next_batch();
+        }
+
+        for corner in &self.data.quad_vertex_positions {
+            self.data.circle_vertex_buffer_base.push(CircleVertex {
+                world_position: glm::vec4_to_vec3(&(transform * corner)),
+                local_position: glm::vec4_to_vec3(corner) * 2.0,
+                color,
+                thickness,
+                fade,
+                entity_id,
+            });
+        }
+        self.data.circle_index_count += QUAD_INDEX_COUNT as u32;
+        self.stats.increment_circle_count();
+    }
+
+    pub fn draw_line(&mut self, p0: &glm::Vec3, p1: &glm::Vec3, color: glm::Vec4) {
+        if self.line_batch_is_full() {
+            self.next_batch();
+        }
+
+        for position in [p0, p1] {
+            self.data.line_vertex_buffer_base.push(LineVertex {
+                position: *position,
+                color,
+                entity_id: -1,
+            });
+        }
+        self.stats.increment_line_count();
+    }
+
+    /// Draws the edges of the unit quad `transform` puts in the world.
+    pub fn draw_rect_outline(&mut self, transform: &glm::Mat4, color: glm::Vec4) {
+        let corners = rect_corners(transform, &self.data.quad_vertex_positions);
+        for i in 0..corners.len() {
+            self.draw_line(&corners[i], &corners[(i + 1) % corners.len()], color);
+        }
+    }
+
+    pub fn line_width(&self) -> f32 {
+        self.data.line_width
+    }
+
+    /// Width of the lines drawn from now on, in pixels. Drivers only have to support 1, wider
+    /// lines may come out 1 pixel wide.
+    pub fn set_line_width(&mut self, width: f32) {
+        // Lines already in the batch are drawn at the width they were given at.
+        if width != self.data.line_width && !self.data.line_vertex_buffer_base.is_empty() {
+            self.next_batch();
+        }
+        self.data.line_width = width;
+    }
+
+    // Whether another quad would overflow the vertex buffer.
+    fn batch_is_full(&self) -> bool {
+        self.data.quad_vertex_buffer_base.len() + QUAD_VERTEX_COUNT > self.config.max_vertices()
+    }
+
+    fn circle_batch_is_full(&self) -> bool {
+        self.data.circle_vertex_buffer_base.len() + QUAD_VERTEX_COUNT > self.config.max_vertices()
+    }
+
+    fn line_batch_is_full(&self) -> bool {
+        self.data.line_vertex_buffer_base.len() + 2 > self.config.max_vertices()
+    }
+
+    fn draw_indexed(&self, vertex_array: &GLVertexArray, index_count: u32) {
         match &self.backend {
             RendererBackend::OpenGL(opengl_api) => {
-                opengl_api.draw_indexed(&*self.data.quad_vertex_array, self.data.quad_index_count as usize);
+                opengl_api.draw_indexed(vertex_array, index_count as usize);
             },
             _ => { panic!("Unsupported renderer backend") },
         }
@@ -355,7 +560,7 @@ impl Renderer2D {
     fn clear_color(&self) {
         match &self.backend {
             RendererBackend::OpenGL(ogl) => {
-                ogl.set_clear_color(&glm::Vec4::new(0.2, 0.3, 0.5, 1.0));
+                ogl.set_clear_color(&self.clear_color);
             },
             _ => { },
         }
@@ -382,13 +587,40 @@ impl Renderer2D {
 
 }
 
-fn to_bytes(quad_vertices: &[QuadVertex]) -> &[u8] {
+// Textures are the same if they're the same GL texture, not if they look the same.
+fn find_texture_slot<T>(slots: &[Rc<T>], texture: &Rc<T>) -> Option<usize> {
+    slots.iter().position(|slot| Rc::ptr_eq(slot, texture))
+}
+
+// The corners of a quad in the world, in order around it.
+fn rect_corners(transform: &glm::Mat4, quad_vertex_positions: &[glm::Vec4; 4]) -> [glm::Vec3; 4] {
+    quad_vertex_positions.map(|corner| glm::vec4_to_vec3(&(transform * corner)))
+}
+
+// Bytes in a vertex buffer of `T`s big enough for a batch. GL sizes buffers with an i32, so a
+// batch too big for one is a bad config rather than something to wrap around. Anything that fits
+// has few enough vertices for 32 bit indices too.
+fn vertex_buffer_size<T>(config: &Renderer2DConfig) -> i32 {
+    config.max_quads.checked_mul(QUAD_VERTEX_COUNT * std::mem::size_of::<T>())
+        .and_then(|size| i32::try_from(size).ok())
+        .unwrap_or_else(|| panic!("{} quads don't fit in a vertex buffer", config.max_quads))
+}
 
-    println!("quad vertices size: {}", std::mem::size_of::<QuadVertex>() * quad_vertices.len());
+// Two triangles per quad, sharing the first and third of its four vertices.
+fn quad_indices(max_quads: usize) -> Vec<u32> {
+    let mut indices = Vec::with_capacity(max_quads * QUAD_INDEX_COUNT);
+    for quad in 0..max_quads as u32 {
+        let offset = quad * QUAD_VERTEX_COUNT as u32;
+        indices.extend_from_slice(&[offset, offset + 1, offset + 2, offset + 2, offset + 3, offset]);
+    }
+    indices
+}
+
+fn to_bytes<T: Copy>(vertices: &[T]) -> &[u8] {
     unsafe {
         std::slice::from_raw_parts(
-            quad_vertices.as_ptr() as *const u8,
-            std::mem::size_of::<QuadVertex>() * quad_vertices.len(),
+            vertices.as_ptr() as *const u8,
+            std::mem::size_of_val(vertices),
         )
     }
 }
@@ -396,11 +628,20 @@ fn to_bytes(quad_vertices: &[QuadVertex]) -> &[u8] {
 
 
 impl RenderStats {
-    fn new() -> Self {
-        RenderStats {
-            draw_calls: 0,
-            quad_count: 0,
-        }
+    pub fn draw_calls(&self) -> usize {
+        self.draw_calls
+    }
+
+    pub fn quad_count(&self) -> usize {
+        self.quad_count
+    }
+
+    pub fn circle_count(&self) -> usize {
+        self.circle_count
+    }
+
+    pub fn line_count(&self) -> usize {
+        self.line_count
     }
 
     fn increment_draw_calls(&mut self) {
@@ -411,12 +652,21 @@ impl RenderStats {
         self.quad_count += 1
     }
 
-    fn total_vertex_count(&self) -> usize {
-        self.quad_count * 4
+    fn increment_circle_count(&mut self) {
+        self.circle_count += 1
+    }
+
+    fn increment_line_count(&mut self) {
+        self.line_count += 1
+    }
+
+    // Circles are drawn on quads too.
+    pub fn total_vertex_count(&self) -> usize {
+        (self.quad_count + self.circle_count) * QUAD_VERTEX_COUNT + self.line_count * 2
     }
 
-    fn total_index_count(&self) -> usize {
-        self.quad_count * 6
+    pub fn total_index_count(&self) -> usize {
+        (self.quad_count + self.circle_count) * QUAD_INDEX_COUNT
     }
 }
 
@@ -424,7 +674,72 @@ impl RenderStats {
 
 impl fmt::Display for RenderStats {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "draw calls: {}\nquad count:{}\ntotal vertices: {}\ntotal indices: {}", 
-            self.draw_calls, self.quad_count, self.total_vertex_count(), self.total_index_count())
+        write!(f, "draw calls: {}\nquad count:{}\ncircle count:{}\nline count:{}\ntotal vertices: {}\ntotal indices: {}", 
+            self.draw_calls, self.quad_count, self.circle_count, self.line_count, self.total_vertex_count(), self.total_index_count())
+    }
+}
+
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quad_indices_match_batch_size() {
+        let indices = quad_indices(3);
+        assert_eq!(3 * QUAD_INDEX_COUNT, indices.len());
+        assert_eq!(&[0, 1, 2, 2, 3, 0], &indices[0..6]);
+        assert_eq!(&[8, 9, 10, 10, 11, 8], &indices[12..18]);
+        assert!(indices.iter().all(|&i| (i as usize) < 3 * QUAD_VERTEX_COUNT));
+    }
+
+    #[test]
+    fn vertex_buffer_size_refuses_to_wrap() {
+        let config = Renderer2DConfig { max_quads: 10, ..Default::default() };
+        assert_eq!(10 * 4 * 48, vertex_buffer_size::<QuadVertex>(&config));
+
+        let too_big = Renderer2DConfig { max_quads: i32::MAX as usize / 4, ..Default::default() };
+        assert!(std::panic::catch_unwind(|| vertex_buffer_size::<QuadVertex>(&too_big)).is_err());
+    }
+
+    #[test]
+    fn texture_slots_go_by_identity() {
+        let white = Rc::new(0);
+        let checkerboard = Rc::new(0);
+        let slots = vec![white.clone(), checkerboard.clone()];
+        assert_eq!(Some(0), find_texture_slot(&slots, &white));
+        assert_eq!(Some(1), find_texture_slot(&slots, &checkerboard.clone()));
+        assert_eq!(None, find_texture_slot(&slots, &Rc::new(0)));
+    }
+
+    #[test]
+    fn rect_corners_go_around_the_quad() {
+        let positions = [
+            glm::Vec4::new(-0.5, -0.5, 0.0, 1.0),
+            glm::Vec4::new(0.5, -0.5, 0.0, 1.0),
+            glm::Vec4::new(0.5, 0.5, 0.0, 1.0),
+            glm::Vec4::new(-0.5, 0.5, 0.0, 1.0),
+        ];
+        let transform = glm::translate(&glm::Mat4::identity(), &glm::Vec3::new(1.0, 2.0, 0.0));
+        let transform = glm::scale(&transform, &glm::Vec3::new(4.0, 2.0, 1.0));
+        let corners = rect_corners(&transform, &positions);
+        assert_eq!(glm::Vec3::new(-1.0, 1.0, 0.0), corners[0]);
+        assert_eq!(glm::Vec3::new(3.0, 1.0, 0.0), corners[1]);
+        assert_eq!(glm::Vec3::new(3.0, 3.0, 0.0), corners[2]);
+        assert_eq!(glm::Vec3::new(-1.0, 3.0, 0.0), corners[3]);
+    }
+
+    #[test]
+    fn stats_count_vertices_and_indices() {
+        let mut stats = RenderStats::default();
+        stats.increment_quad_count();
+        stats.increment_quad_count();
+        stats.increment_circle_count();
+        stats.increment_line_count();
+        stats.increment_draw_calls();
+        assert_eq!(1, stats.draw_calls());
+        assert_eq!(14, stats.total_vertex_count());
+        assert_eq!(18, stats.total_index_count());
     }
 }
@@ -116,7 +116,8 @@ void main()
 
 
 
-pub const FRAGMENT_SRC: &str = "
+// The sampler array and the switch picking from it are filled in by `quad_fragment_src`.
+const FRAGMENT_TEMPLATE: &str = "
 #version 450 core
 
 layout(location = 0) out vec4 o_Color;
@@ -133,47 +134,15 @@ layout (location = 0) in VertexOutput Input;
 layout (location = 3) in flat float v_TexIndex;
 layout (location = 4) in flat int v_EntityID;
 
-layout (binding = 0) uniform sampler2D u_Textures[32];
+layout (binding = 0) uniform sampler2D u_Textures[{TEXTURE_SLOTS}];
 
 void main()
 {
 	vec4 texColor = Input.Color;
 
-	// switch(int(v_TexIndex))
-	// {
-	// 	case  0: texColor *= texture(u_Textures[ 0], Input.TexCoord * Input.TilingFactor); break;
-	// 	case  1: texColor *= texture(u_Textures[ 1], Input.TexCoord * Input.TilingFactor); break;
-	// 	case  2: texColor *= texture(u_Textures[ 2], Input.TexCoord * Input.TilingFactor); break;
-	// 	case  3: texColor *= texture(u_Textures[ 3], Input.TexCoord * Input.TilingFactor); break;
-	// 	case  4: texColor *= texture(u_Textures[ 4], Input.TexCoord * Input.TilingFactor); break;
-	// 	case  5: texColor *= texture(u_Textures[ 5], Input.TexCoord * Input.TilingFactor); break;
-	// 	case  6: texColor *= texture(u_Textures[ 6], Input.TexCoord * Input.TilingFactor); break;
-	// 	case  7: texColor *= texture(u_Textures[ 7], Input.TexCoord * Input.TilingFactor); break;
-	// 	case  8: texColor *= texture(u_Textures[ 8], Input.TexCoord * Input.TilingFactor); break;
-	// 	case  9: texColor *= texture(u_Textures[ 9], Input.TexCoord * Input.TilingFactor); break;
-	// 	case 10: texColor *= texture(u_Textures[10], Input.TexCoord * Input.TilingFactor); break;
-	// 	case 11: texColor *= texture(u_Textures[11], Input.TexCoord * Input.TilingFactor); break;
-	// 	case 12: texColor *= texture(u_Textures[12], Input.TexCoord * Input.TilingFactor); break;
-	// 	case 13: texColor *= texture(u_Textures[13], Input.TexCoord * Input.TilingFactor); break;
-	// 	case 14: texColor *= texture(u_Textures[14], Input.TexCoord * Input.TilingFactor); break;
-	// 	case 15: texColor *= texture(u_Textures[15], Input.TexCoord * Input.TilingFactor); break;
-	// 	case 16: texColor *= texture(u_Textures[16], Input.TexCoord * Input.TilingFactor); break;
-	// 	case 17: texColor *= texture(u_Textures[17], Input.TexCoord * Input.TilingFactor); break;
-	// 	case 18: texColor *= texture(u_Textures[18], Input.TexCoord * Input.TilingFactor); break;
-	// 	case 19: texColor *= texture(u_Textures[19], Input.TexCoord * Input.TilingFactor); break;
-	// 	case 20: texColor *= texture(u_Textures[20], Input.TexCoord * Input.TilingFactor); break;
-	// 	case 21: texColor *= texture(u_Textures[21], Input.TexCoord * Input.TilingFactor); break;
-	// 	case 22: texColor *= texture(u_Textures[22], Input.TexCoord * Input.TilingFactor); break;
-	// 	case 23: texColor *= texture(u_Textures[23], Input.TexCoord * Input.TilingFactor); break;
-	// 	case 24: texColor *= texture(u_Textures[24], Input.TexCoord * Input.TilingFactor); break;
-	// 	case 25: texColor *= texture(u_Textures[25], Input.TexCoord * Input.TilingFactor); break;
-	// 	case 26: texColor *= texture(u_Textures[26], Input.TexCoord * Input.TilingFactor); break;
-	// 	case 27: texColor *= texture(u_Textures[27], Input.TexCoord * Input.TilingFactor); break;
-	// 	case 28: texColor *= texture(u_Textures[28], Input.TexCoord * Input.TilingFactor); break;
-	// 	case 29: texColor *= texture(u_Textures[29], Input.TexCoord * Input.TilingFactor); break;
-	// 	case 30: texColor *= texture(u_Textures[30], Input.TexCoord * Input.TilingFactor); break;
-	// 	case 31: texColor *= texture(u_Textures[31], Input.TexCoord * Input.TilingFactor); break;
-	// }
+	switch(int(v_TexIndex))
+	{
+{TEXTURE_CASES}	}
 
 	if (texColor.a == 0.0)
 		discard;
@@ -182,3 +151,155 @@ void main()
 	o_EntityID = v_EntityID;
 }
 \0";
+
+
+
+/// The quad fragment shader, sampling from `texture_slots` textures. Drivers only have to
+/// support 16, and a sampler array any bigger than what they have can fail to link.
+pub fn quad_fragment_src(texture_slots: usize) -> String {
+    let cases: String = (0..texture_slots)
+        .map(|slot| format!(
+            "\t\tcase {slot}: texColor *= texture(u_Textures[{slot}], Input.TexCoord * Input.TilingFactor); break;\n"
+        ))
+        .collect();
+    FRAGMENT_TEMPLATE
+        .replace("{TEXTURE_SLOTS}", &texture_slots.to_string())
+        .replace("{TEXTURE_CASES}", &cases)
+}
+
+
+
+pub const CIRCLE_VERTEX_SRC: &str = "
+#version 450 core
+
+layout(location = 0) in vec3 a_WorldPosition;
+layout(location = 1) in vec3 a_LocalPosition;
+layout(location = 2) in vec4 a_Color;
+layout(location = 3) in float a_Thickness;
+layout(location = 4) in float a_Fade;
+layout(location = 5) in int a_EntityID;
+
+layout(std140, binding = 0) uniform Camera
+{
+	mat4 u_ViewProjection;
+};
+
+struct VertexOutput
+{
+	vec3 LocalPosition;
+	vec4 Color;
+	float Thickness;
+	float Fade;
+};
+
+layout (location = 0) out VertexOutput Output;
+layout (location = 4) out flat int v_EntityID;
+
+void main()
+{
+	Output.LocalPosition = a_LocalPosition;
+	Output.Color = a_Color;
+	Output.Thickness = a_Thickness;
+	Output.Fade = a_Fade;
+	v_EntityID = a_EntityID;
+
+	gl_Position = u_ViewProjection * vec4(a_WorldPosition, 1.0);
+}
+\0";
+
+
+
+pub const CIRCLE_FRAGMENT_SRC: &str = "
+#version 450 core
+
+layout(location = 0) out vec4 o_Color;
+layout(location = 1) out int o_EntityID;
+
+struct VertexOutput
+{
+	vec3 LocalPosition;
+	vec4 Color;
+	float Thickness;
+	float Fade;
+};
+
+layout (location = 0) in VertexOutput Input;
+layout (location = 4) in flat int v_EntityID;
+
+void main()
+{
+	// Distance in from the edge, 0 on it and 1 at the centre.
+	float distance = 1.0 - length(Input.LocalPosition);
+	// smoothstep is undefined unless its first edge is below its second.
+	float fade = max(Input.Fade, 1e-4);
+	float circle = smoothstep(0.0, fade, distance);
+	circle *= 1.0 - smoothstep(Input.Thickness, Input.Thickness + fade, distance);
+
+	if (circle == 0.0)
+		discard;
+
+	o_Color = Input.Color;
+	o_Color.a *= circle;
+	o_EntityID = v_EntityID;
+}
+\0";
+
+
+
+pub const LINE_VERTEX_SRC: &str = "
+#version 450 core
+
+layout(location = 0) in vec3 a_Position;
+layout(location = 1) in vec4 a_Color;
+layout(location = 2) in int a_EntityID;
+
+layout(std140, binding = 0) uniform Camera
+{
+	mat4 u_ViewProjection;
+};
+
+layout (location = 0) out vec4 v_Color;
+layout (location = 1) out flat int v_EntityID;
+
+void main()
+{
+	v_Color = a_Color;
+	v_EntityID = a_EntityID;
+
+	gl_Position = u_ViewProjection * vec4(a_Position, 1.0);
+}
+\0";
+
+
+
+pub const LINE_FRAGMENT_SRC: &str = "
+#version 450 core
+
+layout(location = 0) out vec4 o_Color;
+layout(location = 1) out int o_EntityID;
+
+layout (location = 0) in vec4 v_Color;
+layout (location = 1) in flat int v_EntityID;
+
+void main()
+{
+	o_Color = v_Color;
+	o_EntityID = v_EntityID;
+}
+\0";
+
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quad_fragment_src_samples_every_slot() {
+        let src = quad_fragment_src(16);
+        assert!(src.contains("uniform sampler2D u_Textures[16];"));
+        assert!(src.contains("case 15: texColor *= texture(u_Textures[15]"));
+        assert!(!src.contains("case 16:"));
+        assert!(!src.contains("{TEXTURE"));
+    }
+}
@@ -48,13 +48,33 @@ pub struct GLTexture {
 
 impl GLTexture {
     pub fn new(gl: Rc<Context>, filepath: &str) -> Self {
-        let path = Path::new(filepath);
-        let img = image::open(path)
-            .expect(&format!("Failed to open image: {}", filepath));
+        Self::open(gl, filepath)
+            .unwrap_or_else(|e| panic!("Failed to open image: {}: {}", filepath, e))
+    }
+
+    /// Loads an image file into a texture. Images that aren't 8 bit RGB or RGBA are converted to
+    /// RGBA first.
+    pub fn open(gl: Rc<Context>, filepath: &str) -> Result<Self, image::ImageError> {
+        let img = image::open(Path::new(filepath))?;
+        let img = match img.color() {
+            image::ColorType::Rgb8 | image::ColorType::Rgba8 => img,
+            _ => image::DynamicImage::ImageRgba8(img.to_rgba8()),
+        };
 
         let (width, height) = img.dimensions();
         let format = TextureFormat::from(img.color());
+        let mut texture = Self::with_data(gl, width, height, format, img.as_bytes());
+        texture.path = filepath.into();
+        Ok(texture)
+    }
 
+    /// A single opaque white texel, so untextured quads can go through the same shader as
+    /// textured ones.
+    pub fn white(gl: Rc<Context>) -> Self {
+        Self::with_data(gl, 1, 1, TextureFormat::RGBA8, &[0xff; 4])
+    }
+
+    fn with_data(gl: Rc<Context>, width: u32, height: u32, format: TextureFormat, bytes: &[u8]) -> Self {
         unsafe {
             let texture = gl.create_texture().
                 expect("Failed to create open gl texture.");
@@ -63,6 +83,8 @@ impl GLTexture {
             gl.texture_parameter_i32(texture, glow::TEXTURE_MAG_FILTER, glow::LINEAR as i32);
             gl.texture_parameter_i32(texture, glow::TEXTURE_WRAP_S, glow::REPEAT as i32);
             gl.texture_parameter_i32(texture, glow::TEXTURE_WRAP_T, glow::REPEAT as i32);
+            // Rows of RGB8 images aren't padded to 4 bytes.
+            gl.pixel_store_i32(glow::UNPACK_ALIGNMENT, 1);
             gl.texture_sub_image_2d(
                 texture, 
                 0, 0, 0, 
@@ -70,12 +92,12 @@ impl GLTexture {
                 height as i32, 
                 format.to_gl_data_format(), 
                 glow::UNSIGNED_BYTE, 
-                PixelUnpackData::Slice(Some(img.as_bytes()))
+                PixelUnpackData::Slice(Some(bytes))
             );
 
             Self {
                 gl,
-                path: filepath.into(),
+                path: String::new(),
                 width,
                 height,
                 format,
@@ -87,6 +109,16 @@ impl GLTexture {
 
 
 
+impl Drop for GLTexture {
+    fn drop(&mut self) {
+        unsafe {
+            self.gl.delete_texture(self.texture);
+        }
+    }
+}
+
+
+
 impl Texture for GLTexture {
     fn bind(&self, slot: u32) {
         unsafe {
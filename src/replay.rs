@@ -0,0 +1,691 @@
+// Recording matches and playing them back. A replay is the match config (seed included), the
+// bindings the humans played with and, tick by tick, the input their paddles read. Playing it
+// through a fresh `GameData` at the same fixed step gives back the exact same match, so replays
+// can go with bug reports and physics changes can be checked by diffing `dump_state`.
+//
+// Replays are plain text:
+//
+//     [match]
+//     version = 4
+//     tick_rate = 120
+//     seed = 1234
+//     ...
+//
+//     [player1]
+//     MoveUp = physical:Q, ...
+//
+//     [ticks]
+//     0 input keys=physical:Q,key:Q
+//     0 cursor 0.25
+//     840 pause
+//     900 bind player1 MoveUp = key:W
+//     900 unpause
+//     1200 serve player2
+//     7560 end
+//
+// `[match]` holds the `MatchConfig`, the binding sections are the same as in the input config
+// (see `core::action`) and `[ticks]` lists what happened right before each tick ran. Input and
+// cursor lines hold until the next one, `bind` lines are bindings changed mid match and `end` is
+// how many ticks the match ran for.
+use crate::controls::SharedInput;
+use crate::core::action::{Action, Binding, ConfigError, InputConfig};
+use crate::core::input::InputState;
+use crate::core::timestep::FixedTimestep;
+use crate::sim::{AiDifficulty, AiParams, GameData, GameMode, MatchConfig, ServeRotation};
+
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+
+/// Bumped whenever the format changes or the simulation stops matching older replays.
+pub const REPLAY_VERSION: u32 = 4;
+
+/// Most ticks `ReplayPlayer::update` runs in one frame, so fast playback can't stall a frame.
+const MAX_TICKS_PER_FRAME: u32 = 64;
+
+/// Something done to the match between ticks, rather than through a paddle.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MatchCommand {
+    Pause,
+    Unpause,
+    Restart,
+    Mouse(bool), // the first human paddle switches to the mouse, or back to its bindings
+    Serve(usize), // the player serves without waiting out the countdown
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ReplayEvent {
+    Input(Box<InputState>),
+    Cursor(Option<f32>), // court height under the cursor
+    Command(MatchCommand),
+    Bind { player: Option<usize>, action: Action, bindings: Vec<Binding> }, // `None` is the global bindings
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Replay {
+    pub config: MatchConfig,
+    pub tick_rate: u32,
+    pub controls: InputConfig,
+    events: Vec<(u64, ReplayEvent)>, // tick the event happens before, in order
+    length: u64,                     // ticks in the match
+}
+
+impl Replay {
+    pub fn new(config: MatchConfig, tick_rate: u32, controls: InputConfig) -> Self {
+        Replay {
+            config,
+            tick_rate,
+            controls,
+            events: Vec::new(),
+            length: 0,
+        }
+    }
+
+    pub fn events(&self) -> &[(u64, ReplayEvent)] {
+        &self.events
+    }
+
+    pub fn length(&self) -> u64 {
+        self.length
+    }
+
+    /// Reads a replay in the format described at the top of this file.
+    pub fn parse(text: &str) -> Result<Replay, ConfigError> {
+        let mut section = "";
+        let mut settings = HashMap::new();
+        let mut controls = String::new(); // binding lines, blank elsewhere so line numbers match
+        let mut events = Vec::new();
+        let mut length = None;
+
+        for (i, line) in text.lines().enumerate() {
+            let error = |message: String| ConfigError::Parse { line: i + 1, message };
+            let trimmed = line.trim();
+            if let Some(name) = trimmed.strip_prefix('[').and_then(|line| line.strip_suffix(']')) {
+                section = match name.trim() {
+                    "match" => "match",
+                    "ticks" => "ticks",
+                    _ => "controls",
+                };
+            }
+
+            if section == "controls" {
+                controls.push_str(line);
+            }
+            controls.push('\n');
+            if trimmed.is_empty() || trimmed.starts_with('#') || trimmed.starts_with('[') {
+                continue;
+            }
+
+            match section {
+                "match" => {
+                    let (name, value) = trimmed.split_once('=')
+                        .ok_or_else(|| error(format!("expected <name> = <value>, got \"{}\"", trimmed)))?;
+                    settings.insert(name.trim().to_string(), (i + 1, value.trim().to_string()));
+                },
+                "ticks" => {
+                    if length.is_some() {
+                        return Err(error("nothing can come after end".to_string()));
+                    }
+                    let (tick, event) = trimmed.split_once(' ').unwrap_or((trimmed, ""));
+                    let tick = tick.parse::<u64>().map_err(|_| error(format!("bad tick \"{}\"", tick)))?;
+                    if events.last().is_some_and(|(last, _)| *last > tick) {
+                        return Err(error(format!("tick {} is out of order", tick)));
+                    }
+                    match parse_event(event.trim()).map_err(error)? {
+                        Some(event) => events.push((tick, event)),
+                        None => length = Some(tick),
+                    }
+                },
+                "controls" => { },
+                _ => return Err(error("expected a [match] section first".to_string())),
+            }
+        }
+
+        let mut replay = Replay::new(parse_config(&settings)?, 0, InputConfig::parse(&controls)?);
+        replay.tick_rate = setting(&settings, "tick_rate")?.unwrap_or(0);
+        if replay.tick_rate == 0 {
+            return Err(ConfigError::Parse { line: 0, message: "missing tick_rate".to_string() });
+        }
+        replay.events = events;
+        replay.length = length.ok_or_else(|| ConfigError::Parse {
+            line: text.lines().count(),
+            message: "missing end, the replay was cut short".to_string(),
+        })?;
+        Ok(replay)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Replay, ConfigError> {
+        Replay::parse(&std::fs::read_to_string(path)?)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        std::fs::write(path, self.to_string())
+    }
+}
+
+fn parse_event(text: &str) -> Result<Option<ReplayEvent>, String> {
+    let (kind, value) = text.split_once(' ').unwrap_or((text, ""));
+    let event = match (kind, value.trim()) {
+        ("end", "") => return Ok(None),
+        ("input", state) => ReplayEvent::Input(Box::new(InputState::parse(state)?)),
+        ("cursor", "none") => ReplayEvent::Cursor(None),
+        ("cursor", y) => ReplayEvent::Cursor(Some(y.parse().map_err(|_| format!("bad cursor height \"{}\"", y))?)),
+        ("pause", "") => ReplayEvent::Command(MatchCommand::Pause),
+        ("unpause", "") => ReplayEvent::Command(MatchCommand::Unpause),
+        ("restart", "") => ReplayEvent::Command(MatchCommand::Restart),
+        ("mouse", "on") => ReplayEvent::Command(MatchCommand::Mouse(true)),
+        ("mouse", "off") => ReplayEvent::Command(MatchCommand::Mouse(false)),
+        ("serve", "player1") => ReplayEvent::Command(MatchCommand::Serve(0)),
+        ("serve", "player2") => ReplayEvent::Command(MatchCommand::Serve(1)),
+        ("bind", binding) => {
+            let (section, binding) = binding.split_once(' ').unwrap_or((binding, ""));
+            let player = match section {
+                "global" => None,
+                "player1" => Some(0),
+                "player2" => Some(1),
+                _ => return Err(format!("unknown section \"{}\"", section)),
+            };
+            let (action, bindings) = binding.split_once('=')
+                .ok_or_else(|| format!("expected <action> = <bindings>, got \"{}\"", binding))?;
+            let action = Action::from_name(action.trim()).ok_or_else(|| format!("unknown action \"{}\"", action.trim()))?;
+            let bindings = bindings.split(',')
+                .filter(|binding| !binding.trim().is_empty())
+                .map(Binding::parse)
+                .collect::<Result<_, _>>()?;
+            ReplayEvent::Bind { player, action, bindings }
+        },
+        _ => return Err(format!("unknown event \"{}\"", text)),
+    };
+    Ok(Some(event))
+}
+
+// `name`'s value, if it's there. `settings` maps names to their line and value.
+fn setting<T: std::str::FromStr>(settings: &HashMap<String, (usize, String)>, name: &str) -> Result<Option<T>, ConfigError> {
+    let Some((line, value)) = settings.get(name) else {
+        return Ok(None);
+    };
+    value.parse().map(Some).map_err(|_| ConfigError::Parse {
+        line: *line,
+        message: format!("bad value for {}: \"{}\"", name, value),
+    })
+}
+
+fn parse_config(settings: &HashMap<String, (usize, String)>) -> Result<MatchConfig, ConfigError> {
+    let version: Option<u32> = setting(settings, "version")?;
+    if version != Some(REPLAY_VERSION) {
+        let line = settings.get("version").map_or(0, |(line, _)| *line);
+        let message = format!("expected replay version {}, got {:?}", REPLAY_VERSION, version);
+        return Err(ConfigError::Parse { line, message });
+    }
+
+    let mut config = MatchConfig::default();
+    config.seed = setting(settings, "seed")?.unwrap_or(config.seed);
+    config.players = setting(settings, "players")?.unwrap_or(config.players);
+    config.rules.target_score = setting(settings, "target_score")?.unwrap_or(config.rules.target_score);
+    config.rules.win_by = setting(settings, "win_by")?.unwrap_or(config.rules.win_by);
+    config.rules.best_of = setting(settings, "best_of")?.unwrap_or(config.rules.best_of);
+    config.bounce.english = setting(settings, "english")?.unwrap_or(config.bounce.english);
+    config.bounce.max_angle = setting(settings, "max_angle")?.unwrap_or(config.bounce.max_angle);
+    config.bounce.velocity_transfer = setting(settings, "velocity_transfer")?.unwrap_or(config.bounce.velocity_transfer);
+    config.bounce.speedup = setting(settings, "speedup")?.unwrap_or(config.bounce.speedup);
+    config.bounce.max_speed = setting(settings, "max_speed")?.unwrap_or(config.bounce.max_speed);
+    config.serve.min_angle = setting(settings, "serve_min_angle")?.unwrap_or(config.serve.min_angle);
+    config.serve.max_angle = setting(settings, "serve_max_angle")?.unwrap_or(config.serve.max_angle);
+    config.serve.min_speed = setting(settings, "serve_min_speed")?.unwrap_or(config.serve.min_speed);
+    config.serve.max_speed = setting(settings, "serve_max_speed")?.unwrap_or(config.serve.max_speed);
+    config.power_ups.spawn_interval = setting(settings, "power_up_interval")?.unwrap_or(config.power_ups.spawn_interval);
+    config.power_ups.lifetime = setting(settings, "power_up_lifetime")?.unwrap_or(config.power_ups.lifetime);
+    config.power_ups.duration = setting(settings, "power_up_duration")?.unwrap_or(config.power_ups.duration);
+    config.power_ups.max_on_court = setting(settings, "max_power_ups")?.unwrap_or(config.power_ups.max_on_court);
+    config.power_ups.max_balls = setting(settings, "max_balls")?.unwrap_or(config.power_ups.max_balls);
+
+    if let Some((line, value)) = settings.get("mode") {
+        config.mode = match value.as_str() {
+            "Classic" => GameMode::Classic,
+            "Arcade" => GameMode::Arcade,
+            _ => return Err(ConfigError::Parse { line: *line, message: format!("unknown mode \"{}\"", value) }),
+        };
+    }
+
+    if let Some((line, value)) = settings.get("serve_rotation") {
+        config.rules.serve_rotation = match value.as_str() {
+            "ToLoser" => ServeRotation::ToLoser,
+            "EveryPoint" => ServeRotation::EveryPoint,
+            "EverySet" => ServeRotation::EverySet,
+            _ => return Err(ConfigError::Parse { line: *line, message: format!("unknown serve rotation \"{}\"", value) }),
+        };
+    }
+    if let Some((line, value)) = settings.get("difficulty") {
+        config.difficulty = parse_difficulty(value)
+            .ok_or_else(|| ConfigError::Parse { line: *line, message: format!("bad difficulty \"{}\"", value) })?;
+    }
+    Ok(config)
+}
+
+// `Easy`, `Medium`, `Hard` or `Custom` followed by the five `AiParams` fields.
+fn parse_difficulty(text: &str) -> Option<AiDifficulty> {
+    let mut words = text.split_whitespace();
+    let difficulty = match words.next()? {
+        "Easy" => AiDifficulty::Easy,
+        "Medium" => AiDifficulty::Medium,
+        "Hard" => AiDifficulty::Hard,
+        "Custom" => {
+            let mut number = || words.next()?.parse::<f32>().ok();
+            let (reaction_distance, reaction_delay, prediction_error, max_speed) = (number()?, number()?, number()?, number()?);
+            let predict_walls = words.next()?.parse().ok()?;
+            AiDifficulty::Custom(AiParams { reaction_distance, reaction_delay, prediction_error, max_speed, predict_walls })
+        },
+        _ => return None,
+    };
+    words.next().is_none().then_some(difficulty)
+}
+
+// Floats are written with `{}`, which always reads back as the same bits.
+impl fmt::Display for Replay {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let config = &self.config;
+        writeln!(f, "# gl-pong replay")?;
+        writeln!(f, "[match]")?;
+        writeln!(f, "version = {}", REPLAY_VERSION)?;
+        writeln!(f, "tick_rate = {}", self.tick_rate)?;
+        writeln!(f, "seed = {}", config.seed)?;
+        writeln!(f, "players = {}", config.players)?;
+        match config.difficulty {
+            AiDifficulty::Custom(params) => writeln!(f, "difficulty = Custom {} {} {} {} {}",
+                params.reaction_distance, params.reaction_delay, params.prediction_error,
+                params.max_speed, params.predict_walls)?,
+            difficulty => writeln!(f, "difficulty = {:?}", difficulty)?,
+        }
+        writeln!(f, "target_score = {}", config.rules.target_score)?;
+        writeln!(f, "win_by = {}", config.rules.win_by)?;
+        writeln!(f, "best_of = {}", config.rules.best_of)?;
+        writeln!(f, "serve_rotation = {:?}", config.rules.serve_rotation)?;
+        writeln!(f, "english = {}", config.bounce.english)?;
+        writeln!(f, "max_angle = {}", config.bounce.max_angle)?;
+        writeln!(f, "velocity_transfer = {}", config.bounce.velocity_transfer)?;
+        writeln!(f, "speedup = {}", config.bounce.speedup)?;
+        writeln!(f, "max_speed = {}", config.bounce.max_speed)?;
+        writeln!(f, "serve_min_angle = {}", config.serve.min_angle)?;
+        writeln!(f, "serve_max_angle = {}", config.serve.max_angle)?;
+        writeln!(f, "serve_min_speed = {}", config.serve.min_speed)?;
+        writeln!(f, "serve_max_speed = {}", config.serve.max_speed)?;
+        writeln!(f, "mode = {:?}", config.mode)?;
+        if config.mode == GameMode::Arcade {
+            writeln!(f, "power_up_interval = {}", config.power_ups.spawn_interval)?;
+            writeln!(f, "power_up_lifetime = {}", config.power_ups.lifetime)?;
+            writeln!(f, "power_up_duration = {}", config.power_ups.duration)?;
+            writeln!(f, "max_power_ups = {}", config.power_ups.max_on_court)?;
+            writeln!(f, "max_balls = {}", config.power_ups.max_balls)?;
+        }
+        writeln!(f)?;
+        write!(f, "{}", self.controls)?;
+        writeln!(f)?;
+        writeln!(f, "[ticks]")?;
+        for (tick, event) in &self.events {
+            match event {
+                ReplayEvent::Input(state) => writeln!(f, "{} input {}", tick, state)?,
+                ReplayEvent::Cursor(Some(y)) => writeln!(f, "{} cursor {}", tick, y)?,
+                ReplayEvent::Cursor(None) => writeln!(f, "{} cursor none", tick)?,
+                ReplayEvent::Command(MatchCommand::Pause) => writeln!(f, "{} pause", tick)?,
+                ReplayEvent::Command(MatchCommand::Unpause) => writeln!(f, "{} unpause", tick)?,
+                ReplayEvent::Command(MatchCommand::Restart) => writeln!(f, "{} restart", tick)?,
+                ReplayEvent::Command(MatchCommand::Serve(player)) => {
+                    writeln!(f, "{} serve player{}", tick, player + 1)?
+                },
+                ReplayEvent::Command(MatchCommand::Mouse(on)) => {
+                    writeln!(f, "{} mouse {}", tick, if *on { "on" } else { "off" })?
+                },
+                ReplayEvent::Bind { player, action, bindings } => {
+                    let section = player.map_or("global".to_string(), |player| format!("player{}", player + 1));
+                    let bindings: Vec<_> = bindings.iter().map(Binding::to_string).collect();
+                    writeln!(f, "{} bind {} {} = {}", tick, section, action.name(), bindings.join(", "))?
+                },
+            }
+        }
+        writeln!(f, "{} end", self.length)
+    }
+}
+
+/// Builds a `Replay` while a match is played. Feed it the frame's input before the frame's
+/// ticks, call `tick` after each tick and `command` for anything done to the match in between.
+pub struct Recorder {
+    replay: Replay,
+    input: InputState,     // last input written
+    cursor_y: Option<f32>, // last cursor height written
+}
+
+impl Recorder {
+    pub fn new(config: MatchConfig, tick_rate: u32, controls: InputConfig) -> Self {
+        Recorder {
+            replay: Replay::new(config, tick_rate, controls),
+            input: InputState::default(),
+            cursor_y: None,
+        }
+    }
+
+    /// Ticks recorded so far.
+    pub fn ticks(&self) -> u64 {
+        self.replay.length
+    }
+
+    /// The input the paddles will read from the next tick on. Only changes are written.
+    pub fn input(&mut self, input: &InputState, cursor_y: Option<f32>) {
+        let input = input.snapshot();
+        if input != self.input {
+            self.push(ReplayEvent::Input(Box::new(input.clone())));
+            self.input = input;
+        }
+        // Compared as bits so a cursor that moved by less than a rounding error still counts.
+        if cursor_y.map(f32::to_bits) != self.cursor_y.map(f32::to_bits) {
+            self.push(ReplayEvent::Cursor(cursor_y));
+            self.cursor_y = cursor_y;
+        }
+    }
+
+    pub fn command(&mut self, command: MatchCommand) {
+        self.push(ReplayEvent::Command(command));
+    }
+
+    /// `action` was rebound to `bindings` mid match.
+    pub fn bind(&mut self, player: Option<usize>, action: Action, bindings: &[Binding]) {
+        self.push(ReplayEvent::Bind { player, action, bindings: bindings.to_vec() });
+    }
+
+    pub fn tick(&mut self) {
+        self.replay.length += 1;
+    }
+
+    pub fn finish(self) -> Replay {
+        self.replay
+    }
+
+    fn push(&mut self, event: ReplayEvent) {
+        self.replay.events.push((self.replay.length, event));
+    }
+}
+
+/// Plays a `Replay` back through a headless `GameData`, with pause, seeking and speed control
+/// for watching it.
+pub struct ReplayPlayer {
+    replay: Replay,
+    game: GameData,
+    input: SharedInput,
+    controls: InputConfig, // the replay's bindings with any changes made so far
+    mouse: bool,       // whether the first human paddle is on the mouse
+    tick: u64,         // ticks played so far
+    next_event: usize, // index into the replay's events
+    timestep: FixedTimestep,
+    paused: bool,
+    speed: f32,
+}
+
+impl ReplayPlayer {
+    pub fn new(replay: Replay) -> Self {
+        let timestep = FixedTimestep::new(replay.tick_rate, MAX_TICKS_PER_FRAME);
+        let mut player = ReplayPlayer {
+            game: GameData::new(replay.config),
+            controls: replay.controls.clone(),
+            replay,
+            input: SharedInput::new(),
+            mouse: false,
+            tick: 0,
+            next_event: 0,
+            timestep,
+            paused: false,
+            speed: 1.0f32,
+        };
+        player.rewind();
+        player
+    }
+
+    pub fn game(&self) -> &GameData {
+        &self.game
+    }
+
+    pub fn replay(&self) -> &Replay {
+        &self.replay
+    }
+
+    /// Ticks played so far.
+    pub fn position(&self) -> u64 {
+        self.tick
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.tick >= self.replay.length
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    pub fn set_paused(&mut self, paused: bool) {
+        self.paused = paused;
+    }
+
+    pub fn speed(&self) -> f32 {
+        self.speed
+    }
+
+    /// Playback speed for `update`, 1 for real time. Negative speeds count as 0.
+    pub fn set_speed(&mut self, speed: f32) {
+        self.speed = speed.max(0.0f32);
+    }
+
+    /// How far `update` is between the last tick and the next one, for drawing.
+    pub fn alpha(&self) -> f32 {
+        self.timestep.alpha()
+    }
+
+    /// Plays `frame_delta` seconds of wall time at the current speed. Returns the ticks run.
+    pub fn update(&mut self, frame_delta: f32) -> u32 {
+        if self.paused {
+            return 0;
+        }
+        let steps = self.timestep.advance(frame_delta * self.speed);
+        (0..steps).take_while(|_| self.step()).count() as u32
+    }
+
+    /// Plays one tick. Returns false, without doing anything, once the replay is over.
+    pub fn step(&mut self) -> bool {
+        if self.is_finished() {
+            return false;
+        }
+        while let Some((tick, event)) = self.replay.events.get(self.next_event)
+            && *tick <= self.tick {
+            let event = event.clone();
+            self.apply(event);
+            self.next_event += 1;
+        }
+        self.game.update(self.timestep.dt());
+        // Nothing reacts to a replay's events yet, don't let them pile up.
+        self.game.drain_events();
+        self.tick += 1;
+        true
+    }
+
+    /// Plays whatever is left of the replay.
+    pub fn run_to_end(&mut self) {
+        while self.step() { }
+    }
+
+    /// Jumps to right after tick `tick`. Going backwards replays from the start, as the only way
+    /// to get a match state back is to play up to it.
+    pub fn seek(&mut self, tick: u64) {
+        let tick = tick.min(self.replay.length);
+        if tick < self.tick {
+            self.rewind();
+        }
+        while self.tick < tick && self.step() { }
+    }
+
+    fn rewind(&mut self) {
+        self.game = GameData::new(self.replay.config);
+        self.input = SharedInput::new();
+        self.controls = self.replay.controls.clone();
+        self.mouse = false;
+        self.tick = 0;
+        self.next_event = 0;
+        self.timestep.reset();
+        self.install_controllers();
+    }
+
+    fn install_controllers(&mut self) {
+        let players = self.replay.config.players;
+        for player in 0..(players as usize).min(2) {
+            let controller = self.input.controller(&self.controls, players, player, self.mouse && player == 0);
+            self.game.set_controller(player, controller);
+        }
+    }
+
+    fn apply(&mut self, event: ReplayEvent) {
+        match event {
+            ReplayEvent::Input(state) => self.input.set_state(*state),
+            ReplayEvent::Cursor(y) => self.input.set_cursor_y(y),
+            ReplayEvent::Command(command) => {
+                if let MatchCommand::Mouse(on) = command {
+                    self.mouse = on;
+                    self.install_controllers();
+                }
+                apply_command(&mut self.game, command);
+            },
+            ReplayEvent::Bind { player, action, bindings } => {
+                let map = self.controls.map_mut(player);
+                map.clear(action);
+                for binding in bindings {
+                    map.bind(action, binding);
+                }
+                self.install_controllers();
+            },
+        }
+    }
+}
+
+/// Does `command` to `game`. `MatchCommand::Mouse` is left to whoever owns the controllers.
+pub fn apply_command(game: &mut GameData, command: MatchCommand) {
+    match command {
+        MatchCommand::Pause => game.pause(),
+        MatchCommand::Unpause => game.unpause(),
+        MatchCommand::Restart => game.restart(),
+        MatchCommand::Serve(player) => game.serve_now(player),
+        MatchCommand::Mouse(_) => { },
+    }
+}
+
+/// Everything about where a match stands, one thing per line with exact floats, so the ends of
+/// two runs can be diffed.
+pub fn dump_state(game: &GameData) -> String {
+    let mut lines = vec![
+        format!("state {:?}", game.state()),
+        format!("scores {} {}", game.scores()[0], game.scores()[1]),
+        format!("sets {} {}", game.sets()[0], game.sets()[1]),
+        format!("server {}", game.server()),
+        format!("winner {:?}", game.winner()),
+        format!("rally {}", game.rally()),
+    ];
+    for (i, ball) in game.balls().iter().enumerate() {
+        let name = if i == 0 { "ball".to_string() } else { format!("ball{}", i + 1) };
+        lines.push(format!("{} {} {} {} {}", name, ball.position().x, ball.position().y, ball.velocity().x, ball.velocity().y));
+    }
+    for power_up in game.power_ups() {
+        lines.push(format!("power_up {:?} {} {}", power_up.kind, power_up.position.x, power_up.position.y));
+    }
+    for effect in game.effects() {
+        lines.push(format!("effect player{} {:?} {}", effect.player + 1, effect.kind, effect.time_left));
+    }
+    for (i, player) in game.players().iter().enumerate() {
+        let paddle = player.paddle();
+        lines.push(format!("paddle{} {} {}", i + 1, paddle.position().x, paddle.position().y));
+    }
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::input::{KeyBinding, KeyKind};
+    use crate::sim::MatchRules;
+
+    const TICK_RATE: u32 = 120;
+
+    // Plays a short one player match the way the game does, with the human serving early, holding
+    // a key for a while and pausing once. Returns the final state and the recording.
+    fn record() -> (String, Replay) {
+        let mut config = MatchConfig::new(1, MatchRules::first_to(3, 1));
+        config.seed = 42;
+        let mut controls = InputConfig::default();
+        let mut recorder = Recorder::new(config, TICK_RATE, controls.clone());
+        let mut game = GameData::new(config);
+        let shared = SharedInput::new();
+        game.set_controller(0, shared.controller(&controls, 1, 0, false));
+
+        let held = InputState::parse("keys=key:K,physical:K").unwrap();
+        for frame in 0..600 {
+            let state = if (50..200).contains(&frame) || (520..580).contains(&frame) { held.clone() } else { InputState::default() };
+            shared.set_state(state.clone());
+            recorder.input(&state, None);
+            for _ in 0..2 {
+                game.update(1.0 / TICK_RATE as f32);
+                recorder.tick();
+            }
+            if frame == 310 {
+                // From here on K moves player 1 down instead.
+                let binding = Binding::Key(KeyBinding::Physical(KeyKind::K));
+                controls.players[0].rebind(Action::MoveDown, binding);
+                controls.players[0].unbind(Action::MoveUp, binding);
+                game.set_controller(0, shared.controller(&controls, 1, 0, false));
+                recorder.bind(Some(0), Action::MoveDown, controls.players[0].bindings(Action::MoveDown));
+                recorder.bind(Some(0), Action::MoveUp, controls.players[0].bindings(Action::MoveUp));
+            }
+            let command = match frame {
+                10 => Some(MatchCommand::Serve(game.server())),
+                300 => Some(MatchCommand::Pause),
+                320 => Some(MatchCommand::Unpause),
+                _ => None,
+            };
+            if let Some(command) = command {
+                apply_command(&mut game, command);
+                recorder.command(command);
+            }
+        }
+        (dump_state(&game), recorder.finish())
+    }
+
+    #[test]
+    fn playback_matches_the_recording() {
+        let (recorded, replay) = record();
+        assert_eq!(1200, replay.length());
+
+        let mut player = ReplayPlayer::new(Replay::parse(&replay.to_string()).unwrap());
+        player.run_to_end();
+        assert_eq!(recorded, dump_state(player.game()));
+
+        // Seeking back replays from the start and ends up in the same place.
+        player.seek(500);
+        assert_eq!(500, player.position());
+        player.run_to_end();
+        assert_eq!(recorded, dump_state(player.game()));
+    }
+
+    #[test]
+    fn file_round_trips() {
+        let (_, mut replay) = record();
+        replay.config.difficulty = AiDifficulty::Custom(AiParams { max_speed: 0.1 + 0.2, ..AiDifficulty::Hard.params() });
+        assert_eq!(replay, Replay::parse(&replay.to_string()).unwrap());
+
+        let cut_short = replay.to_string().replace("1200 end", "");
+        assert!(Replay::parse(&cut_short).is_err());
+    }
+
+    #[test]
+    fn update_follows_speed_and_pause() {
+        let (_, replay) = record();
+        let mut player = ReplayPlayer::new(replay);
+        assert_eq!(12, player.update(0.101));
+        player.set_speed(2.0);
+        assert_eq!(24, player.update(0.101));
+        player.set_paused(true);
+        assert_eq!(0, player.update(0.101));
+        assert_eq!(36, player.position());
+    }
+}
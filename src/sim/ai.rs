@@ -0,0 +1,238 @@
+use super::PaddleIntent;
+use super::COURT;
+use super::ball::Ball;
+use super::controller::{MatchView, PaddleController};
+use crate::core::rng::Rng;
+
+/*
+ * This is a computer player.
+ *  - Move the paddle when the ball is moving towards my paddle
+ *  - Move the paddle in the y direction the ball is moving.
+ *
+ * How well it does that is down to `AiParams`: how early it starts tracking, how long it takes
+ * to react, how far off its guess is and how fast it can move.
+ * */
+
+/// How close the paddle has to be to its target before the AI stops moving.
+const DEAD_ZONE: f32 = 0.05;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AiParams {
+    pub reaction_distance: f32, // how far out (along x) the ball is when the AI starts tracking it
+    pub reaction_delay: f32,    // seconds between the ball turning our way and the AI reacting
+    pub prediction_error: f32,  // largest error added to the predicted intercept, in court units
+    pub max_speed: f32,         // share of full paddle speed the AI will use, 0 to 1
+    pub predict_walls: bool,    // whether the prediction follows the ball off the floor and ceiling
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum AiDifficulty {
+    Easy,
+    #[default]
+    Medium,
+    Hard,
+    Custom(AiParams),
+}
+
+impl AiDifficulty {
+    pub fn params(&self) -> AiParams {
+        match self {
+            AiDifficulty::Easy => AiParams {
+                reaction_distance: 1.0,
+                reaction_delay: 0.3,
+                prediction_error: 0.15,
+                max_speed: 0.6,
+                predict_walls: false,
+            },
+            AiDifficulty::Medium => AiParams {
+                reaction_distance: 1.3,
+                reaction_delay: 0.15,
+                prediction_error: 0.08,
+                max_speed: 0.85,
+                predict_walls: false,
+            },
+            AiDifficulty::Hard => AiParams {
+                reaction_distance: 1.6,
+                reaction_delay: 0.05,
+                prediction_error: 0.03,
+                max_speed: 1.0,
+                predict_walls: true,
+            },
+            AiDifficulty::Custom(params) => *params,
+        }
+    }
+}
+
+pub struct Ai {
+    params: AiParams,
+    rng: Rng,
+    error: f32,          // offset applied to every prediction for the current approach
+    reaction_time: f32,  // seconds since the ball started heading our way
+    approaching: bool,   // whether the ball was heading our way last tick
+}
+
+impl Ai {
+    pub fn new(difficulty: AiDifficulty, seed: u64) -> Self {
+        Ai {
+            params: difficulty.params(),
+            rng: Rng::new(seed),
+            error: 0.0f32,
+            reaction_time: 0.0f32,
+            approaching: false,
+        }
+    }
+
+    pub fn params(&self) -> &AiParams {
+        &self.params
+    }
+}
+
+impl PaddleController for Ai {
+    fn intent(&mut self, delta: f32, view: &MatchView) -> PaddleIntent {
+        let (paddle, ball) = (&view.paddle, &view.ball);
+        let approaching = ball.velocity.x != 0.0f32 &&
+            paddle.position.x.signum() == ball.velocity.x.signum();
+
+        if approaching && !self.approaching {
+            // New approach: start the reaction clock and pick how wrong we'll be this time.
+            self.reaction_time = 0.0f32;
+            let e = self.params.prediction_error;
+            self.error = if e > 0.0f32 { self.rng.range(-e, e) } else { 0.0f32 };
+        }
+        self.approaching = approaching;
+
+        if !approaching {
+            return PaddleIntent::Idle;
+        }
+
+        self.reaction_time += delta;
+        if self.reaction_time < self.params.reaction_delay ||
+            (ball.position.x - paddle.position.x).abs() > self.params.reaction_distance {
+            return PaddleIntent::Idle;
+        }
+
+        let target_y = predict_intercept(ball, paddle.position.x, self.params.predict_walls) + self.error;
+        let y_diff = target_y - paddle.position.y;
+        if y_diff.abs() <= DEAD_ZONE {
+            return PaddleIntent::Idle;
+        }
+
+        // Don't overshoot the target on the last tick of the approach.
+        let full_step = paddle.speed() * delta;
+        let axis = if full_step > 0.0f32 { (y_diff / full_step).clamp(-1.0f32, 1.0f32) } else { y_diff.signum() };
+        PaddleIntent::Axis(axis * self.params.max_speed)
+    }
+
+    fn is_computer(&self) -> bool {
+        true
+    }
+}
+
+/// Where the ball will be along y when it reaches `x`. With `walls` the prediction follows the
+/// ball off the floor and ceiling, otherwise it assumes a straight line.
+pub fn predict_intercept(ball: &Ball, x: f32, walls: bool) -> f32 {
+    if ball.velocity.x == 0.0f32 {
+        return ball.position.y;
+    }
+    let t = (x - ball.position.x) / ball.velocity.x;
+    let y = ball.position.y + (t * ball.velocity.y);
+    if !walls {
+        return y;
+    }
+
+    // Bouncing between two walls is a triangle wave: fold y back into the court.
+    let low = COURT.min.y + ball.radius;
+    let high = COURT.max.y - ball.radius;
+    let span = high - low;
+    if span <= 0.0f32 {
+        return (low + high) / 2.0f32;
+    }
+    let folded = (y - low).rem_euclid(2.0f32 * span);
+    if folded <= span {
+        low + folded
+    } else {
+        high - (folded - span)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sim::{GameData, MatchConfig};
+    use crate::sim::paddle::Paddle;
+    use nalgebra_glm as glm;
+
+    fn ball_at(position: glm::Vec2, velocity: glm::Vec2) -> Ball {
+        let mut ball = Ball::new(0, 0.02);
+        ball.position = position;
+        ball.velocity = velocity;
+        ball
+    }
+
+    fn paddle() -> Paddle {
+        Paddle::new(1, glm::Vec2::new(0.97, 0.0), glm::Vec2::new(0.0, 1.0), 0.03, 0.2)
+    }
+
+    // The right player's view with the ball at `ball`.
+    fn view(ball: Ball) -> MatchView {
+        MatchView {
+            ball,
+            paddle: paddle(),
+            ..GameData::new(MatchConfig::default()).view(1)
+        }
+    }
+
+    #[test]
+    fn predicts_wall_bounces() {
+        // Heads up at 45 degrees, hits the ceiling and comes back down.
+        let ball = ball_at(glm::Vec2::new(0.0, 0.5), glm::Vec2::new(1.0, 1.0));
+        let straight = predict_intercept(&ball, 0.9, false);
+        assert!((straight - 1.4).abs() < 1e-5);
+        let folded = predict_intercept(&ball, 0.9, true);
+        let ceiling = COURT.max.y - ball.radius;
+        assert!((folded - (ceiling - (1.4 - ceiling))).abs() < 1e-5);
+
+        // Two bounces.
+        let ball = ball_at(glm::Vec2::new(-0.9, 0.0), glm::Vec2::new(1.0, 3.0));
+        let y = predict_intercept(&ball, 0.9, true);
+        assert!(y.abs() <= ceiling);
+    }
+
+    #[test]
+    fn ignores_ball_going_away() {
+        let mut ai = Ai::new(AiDifficulty::Hard, 1);
+        let ball = ball_at(glm::Vec2::new(0.5, 0.8), glm::Vec2::new(-1.0, 0.0));
+        assert_eq!(PaddleIntent::Idle, ai.intent(0.1, &view(ball)));
+    }
+
+    #[test]
+    fn waits_for_reaction_delay_then_tracks() {
+        let params = AiParams {
+            reaction_distance: 2.0,
+            reaction_delay: 0.2,
+            prediction_error: 0.0,
+            max_speed: 0.5,
+            predict_walls: false,
+        };
+        let mut ai = Ai::new(AiDifficulty::Custom(params), 1);
+        let ball = ball_at(glm::Vec2::new(0.0, 0.8), glm::Vec2::new(1.0, 0.0));
+        assert_eq!(PaddleIntent::Idle, ai.intent(0.1, &view(ball)));
+        assert_eq!(PaddleIntent::Axis(0.5), ai.intent(0.15, &view(ball)));
+    }
+
+    #[test]
+    fn same_seed_same_mistakes() {
+        let ball = ball_at(glm::Vec2::new(0.5, 0.3), glm::Vec2::new(1.0, 0.5));
+        let away = ball_at(glm::Vec2::new(0.5, 0.3), glm::Vec2::new(-1.0, 0.5));
+        let mut a = Ai::new(AiDifficulty::Easy, 99);
+        let mut b = Ai::new(AiDifficulty::Easy, 99);
+        for _ in 0..20 {
+            for _ in 0..60 {
+                assert_eq!(a.intent(1.0 / 60.0, &view(ball)), b.intent(1.0 / 60.0, &view(ball)));
+            }
+            a.intent(1.0 / 60.0, &view(away));
+            b.intent(1.0 / 60.0, &view(away));
+            assert_eq!(a.error, b.error);
+        }
+    }
+}
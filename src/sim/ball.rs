@@ -0,0 +1,105 @@
+use super::COURT;
+use crate::physics::Circle;
+
+use nalgebra_glm as glm;
+
+#[derive(Clone, Copy, Debug)]
+pub struct Ball {
+    pub(crate) id: u64,
+    pub(crate) radius: f32,
+    pub(crate) position: glm::Vec2,
+    prev_position: glm::Vec2, // position at the start of the last tick, for interpolation
+    pub(crate) velocity: glm::Vec2, // (speed, angle)
+    pub(crate) last_hit: Option<usize>, // player whose paddle the ball last came off
+    pub(crate) boost: f32, // speed limit multiplier, from `PowerUpKind::SpeedBoost`
+    pub(crate) hold: Option<Hold>, // stuck to a paddle, from `PowerUpKind::Sticky`
+}
+
+/// A ball stuck to `player`'s paddle at `offset` from its centre, for `time_left` more seconds.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct Hold {
+    pub player: usize,
+    pub offset: glm::Vec2,
+    pub time_left: f32,
+}
+
+impl Ball {
+    pub fn new(id: u64, radius: f32) -> Self {
+        Ball {
+            id,
+            radius,
+            position: glm::Vec2::new(0.0, 0.0),
+            prev_position: glm::Vec2::new(0.0, 0.0),
+            velocity: glm::Vec2::zeros(),
+            last_hit: None,
+            boost: 1.0,
+            hold: None,
+        }
+    }
+
+    /// Puts the ball back on the centre spot, ready to leave it at `velocity`.
+    pub fn reset(&mut self, velocity: glm::Vec2) {
+        self.position = glm::Vec2::new(0.0, 0.0);
+        self.prev_position = self.position;
+        self.velocity = velocity;
+        self.last_hit = None;
+        self.boost = 1.0;
+        self.hold = None;
+    }
+
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    pub fn radius(&self) -> f32 {
+        self.radius
+    }
+
+    pub fn position(&self) -> &glm::Vec2 {
+        &self.position
+    }
+
+    /// Blends between where the ball was at the start of the last tick and where it is now.
+    pub fn interpolated_position(&self, alpha: f32) -> glm::Vec2 {
+        glm::lerp(&self.prev_position, &self.position, alpha)
+    }
+
+    pub(crate) fn store_previous(&mut self) {
+        self.prev_position = self.position;
+    }
+
+    pub fn velocity(&self) -> &glm::Vec2 {
+        &self.velocity
+    }
+
+    /// The player whose paddle the ball last came off, `None` since the serve.
+    pub fn last_hit(&self) -> Option<usize> {
+        self.last_hit
+    }
+
+    /// Whether the ball is stuck to a sticky paddle.
+    pub fn is_held(&self) -> bool {
+        self.hold.is_some()
+    }
+
+    pub fn shape(&self) -> Circle {
+        Circle::new(self.position, self.radius)
+    }
+
+    pub fn clamp_position(&mut self) {
+        let half = glm::Vec2::new(self.radius, self.radius);
+        self.position = COURT.shrink(half).closest_point(&self.position);
+    }
+
+    /// Caps the ball's speed at `max_speed`, keeping its direction.
+    pub fn clamp_velocity(&mut self, max_speed: f32) {
+        let speed = self.velocity.norm();
+        if speed > max_speed {
+            self.velocity *= max_speed / speed;
+        }
+    }
+
+    pub fn apply_velocity(&mut self, delta: f32) {
+        self.position += self.velocity * delta;
+    }
+}
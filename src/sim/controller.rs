@@ -0,0 +1,239 @@
+use super::{GameState, PaddleIntent};
+use super::ball::Ball;
+use super::paddle::Paddle;
+
+use std::cell::Cell;
+use std::rc::Rc;
+use std::sync::mpsc::{Receiver, TryRecvError};
+
+/// What a controller gets to see of the match each tick. It's a copy, so a controller can keep
+/// it around or hand it to another thread without holding on to `GameData`.
+#[derive(Clone, Copy, Debug)]
+pub struct MatchView {
+    pub player: usize, // index of the player being controlled, 0 is the left paddle
+    pub state: GameState,
+    pub scores: [u32; 2],
+    pub rally: u32,
+    pub ball: Ball,
+    pub paddle: Paddle,   // the controlled paddle
+    pub opponent: Paddle,
+}
+
+/// Drives one paddle. Asked for an intent once per tick while the ball is in play.
+pub trait PaddleController {
+    fn intent(&mut self, delta: f32, view: &MatchView) -> PaddleIntent;
+
+    /// Whether the paddle is played by the computer rather than a person.
+    fn is_computer(&self) -> bool {
+        false
+    }
+}
+
+/// A fixed intent. `PaddleIntent::Idle` is what a human slot holds until the shell plugs in a
+/// real input source.
+impl PaddleController for PaddleIntent {
+    fn intent(&mut self, _delta: f32, _view: &MatchView) -> PaddleIntent {
+        *self
+    }
+}
+
+/// Plays back a list of intents, one per tick. Useful for replays, demos and tests.
+pub struct ScriptedController {
+    script: Vec<PaddleIntent>,
+    next: usize,
+    repeat: bool,
+}
+
+impl ScriptedController {
+    /// Plays `script` once and then stays idle.
+    pub fn new(script: Vec<PaddleIntent>) -> Self {
+        ScriptedController {
+            script,
+            next: 0,
+            repeat: false,
+        }
+    }
+
+    /// Plays `script` over and over.
+    pub fn repeat(script: Vec<PaddleIntent>) -> Self {
+        ScriptedController {
+            repeat: true,
+            ..ScriptedController::new(script)
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        !self.repeat && self.next >= self.script.len()
+    }
+}
+
+impl PaddleController for ScriptedController {
+    fn intent(&mut self, _delta: f32, _view: &MatchView) -> PaddleIntent {
+        if self.repeat && self.next >= self.script.len() {
+            self.next = 0;
+        }
+        let intent = self.script.get(self.next).copied().unwrap_or_default();
+        self.next += 1;
+        intent
+    }
+}
+
+/// A paddle played from somewhere else, like a network peer. Whatever receives the peer's
+/// input sends intents down the channel; the paddle keeps doing the latest one until another
+/// arrives and stops when the sender goes away.
+pub struct RemoteController {
+    receiver: Receiver<PaddleIntent>,
+    current: PaddleIntent,
+}
+
+impl RemoteController {
+    pub fn new(receiver: Receiver<PaddleIntent>) -> Self {
+        RemoteController {
+            receiver,
+            current: PaddleIntent::Idle,
+        }
+    }
+}
+
+impl PaddleController for RemoteController {
+    fn intent(&mut self, _delta: f32, _view: &MatchView) -> PaddleIntent {
+        loop {
+            match self.receiver.try_recv() {
+                Ok(intent) => self.current = intent,
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    self.current = PaddleIntent::Idle;
+                    break;
+                },
+            }
+        }
+        self.current
+    }
+}
+
+/// Chases a height on the court, like where the mouse cursor is. Whoever owns the other end of
+/// `target` moves it; `None` leaves the paddle where it is.
+pub struct FollowController {
+    target: Rc<Cell<Option<f32>>>,
+    max_speed: f32, // fraction of the paddle's full speed
+    smoothing: f32, // seconds to cover most of the way to a new target, 0 to go straight there
+    smoothed: Option<f32>,
+}
+
+impl FollowController {
+    pub fn new(target: Rc<Cell<Option<f32>>>) -> Self {
+        FollowController {
+            target,
+            max_speed: 1.0f32,
+            smoothing: 0.0f32,
+            smoothed: None,
+        }
+    }
+
+    /// Caps the paddle at `max_speed` times its full speed, clamped to `[0, 1]`.
+    pub fn with_max_speed(mut self, max_speed: f32) -> Self {
+        self.max_speed = max_speed.clamp(0.0f32, 1.0f32);
+        self
+    }
+
+    /// Eases towards new targets over about `seconds` instead of jumping straight at them.
+    pub fn with_smoothing(mut self, seconds: f32) -> Self {
+        self.smoothing = seconds.max(0.0f32);
+        self
+    }
+}
+
+impl PaddleController for FollowController {
+    fn intent(&mut self, delta: f32, view: &MatchView) -> PaddleIntent {
+        let Some(target) = self.target.get() else {
+            self.smoothed = None;
+            return PaddleIntent::Idle;
+        };
+        let target = match self.smoothed {
+            Some(smoothed) if self.smoothing > 0.0f32 => {
+                smoothed + (target - smoothed) * (1.0f32 - (-delta / self.smoothing).exp())
+            },
+            _ => target,
+        };
+        self.smoothed = Some(target);
+
+        let step = view.paddle.speed() * delta;
+        if step <= 0.0f32 {
+            return PaddleIntent::Idle;
+        }
+        let axis = ((target - view.paddle.position().y) / step).clamp(-self.max_speed, self.max_speed);
+        if axis == 0.0f32 { PaddleIntent::Idle } else { PaddleIntent::Axis(axis) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sim::{GameData, MatchConfig};
+
+    use std::sync::mpsc;
+
+    fn view() -> MatchView {
+        GameData::new(MatchConfig::default()).view(0)
+    }
+
+    #[test]
+    fn script_plays_once_or_repeats() {
+        let view = view();
+        let script = vec![PaddleIntent::Up, PaddleIntent::Down];
+
+        let mut once = ScriptedController::new(script.clone());
+        assert_eq!(PaddleIntent::Up, once.intent(0.1, &view));
+        assert_eq!(PaddleIntent::Down, once.intent(0.1, &view));
+        assert!(once.is_finished());
+        assert_eq!(PaddleIntent::Idle, once.intent(0.1, &view));
+
+        let mut looped = ScriptedController::repeat(script);
+        let played: Vec<_> = (0..4).map(|_| looped.intent(0.1, &view)).collect();
+        assert_eq!(vec![PaddleIntent::Up, PaddleIntent::Down, PaddleIntent::Up, PaddleIntent::Down], played);
+    }
+
+    #[test]
+    fn remote_keeps_latest_intent() {
+        let view = view();
+        let (sender, receiver) = mpsc::channel();
+        let mut remote = RemoteController::new(receiver);
+        assert_eq!(PaddleIntent::Idle, remote.intent(0.1, &view));
+
+        sender.send(PaddleIntent::Up).unwrap();
+        sender.send(PaddleIntent::Down).unwrap();
+        assert_eq!(PaddleIntent::Down, remote.intent(0.1, &view));
+        assert_eq!(PaddleIntent::Down, remote.intent(0.1, &view));
+
+        drop(sender);
+        assert_eq!(PaddleIntent::Idle, remote.intent(0.1, &view));
+    }
+
+    #[test]
+    fn follow_is_speed_limited_and_smoothed() {
+        let view = view();
+        let y = view.paddle.position().y;
+        let step = view.paddle.speed() * 0.01;
+        let target = Rc::new(Cell::new(None));
+
+        let mut follow = FollowController::new(target.clone()).with_max_speed(0.5);
+        assert_eq!(PaddleIntent::Idle, follow.intent(0.01, &view));
+        target.set(Some(y + step * 0.25));
+        let PaddleIntent::Axis(axis) = follow.intent(0.01, &view) else {
+            panic!("expected the paddle to move");
+        };
+        assert!((axis - 0.25).abs() < 1e-3);
+        target.set(Some(y - 1.0));
+        assert_eq!(PaddleIntent::Axis(-0.5), follow.intent(0.01, &view));
+
+        // Smoothed, the first jump only gets part of the way.
+        let mut smooth = FollowController::new(target.clone()).with_smoothing(0.1);
+        target.set(Some(y));
+        smooth.intent(0.01, &view);
+        target.set(Some(y + step));
+        let PaddleIntent::Axis(axis) = smooth.intent(0.01, &view) else {
+            panic!("expected the paddle to move");
+        };
+        assert!(axis > 0.0 && axis < 0.2, "moved {} of the way", axis);
+    }
+}
@@ -0,0 +1,34 @@
+// What happened in a match, for anything that wants to react to it (sound, effects, stats, the
+// network, tests) without reaching into the physics. `GameData` queues these as they happen and
+// whoever runs the match drains them with `GameData::drain_events`, usually after every tick.
+use super::GameState;
+use super::powerup::PowerUpKind;
+
+use nalgebra_glm as glm;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MatchEvent {
+    /// The ball left the centre spot towards `receiver`.
+    Served { server: usize, receiver: usize, speed: f32 },
+    /// The ball came off `player`'s paddle at `speed`, having touched it at height `contact_y`.
+    BallHitPaddle { player: usize, speed: f32, contact_y: f32 },
+    /// The ball came off the floor or the ceiling at `speed`, having touched it at `contact_x`.
+    BallHitWall { speed: f32, contact_x: f32 },
+    /// `scorer` won a point, `scores` is the set score after it.
+    PointScored { scorer: usize, scores: [u32; 2] },
+    /// `winner` took a set, `sets` is the set count after it.
+    SetWon { winner: usize, sets: [u32; 2] },
+    MatchWon { winner: usize, sets: [u32; 2] },
+    /// The match moved from one `GameState` to another, e.g. paused or came back from a pause.
+    StateChanged { from: GameState, to: GameState },
+    /// The match was started over with `GameData::restart`.
+    Restarted,
+    /// A power-up showed up on the court.
+    PowerUpSpawned { kind: PowerUpKind, position: glm::Vec2 },
+    /// `player` ran a ball into a power-up.
+    PowerUpCollected { player: usize, kind: PowerUpKind },
+    /// A timed power-up working on `player`'s side ran out.
+    PowerUpExpired { player: usize, kind: PowerUpKind },
+    /// `player`'s shield saved a point and is gone.
+    ShieldBlocked { player: usize },
+}
@@ -0,0 +1,1313 @@
+// Headless match simulation. Nothing in this module may depend on glow, glutin or winit so a
+// match can be stepped from unit tests, bots or a server without a GPU or a window.
+pub mod ai;
+pub mod ball;
+pub mod controller;
+pub mod events;
+pub mod paddle;
+pub mod player;
+pub mod powerup;
+pub mod rules;
+
+pub use ai::{Ai, AiDifficulty, AiParams};
+pub use ball::Ball;
+pub use controller::{FollowController, MatchView, PaddleController, RemoteController, ScriptedController};
+pub use events::MatchEvent;
+pub use paddle::{Paddle, PADDLE_WIDTH, PADDLE_HEIGHT};
+pub use player::Player;
+pub use powerup::{Effect, GameMode, PowerUp, PowerUpConfig, PowerUpKind, POWER_UP_RADIUS};
+pub use rules::{MatchRules, ServeRotation};
+
+use crate::core::rng::Rng;
+use crate::physics::*;
+use ball::Hold;
+use powerup::{GROW_FACTOR, SHRINK_FACTOR, SPEED_BOOST, STICKY_HOLD};
+
+use nalgebra_glm as glm;
+
+/// The playing field. Paddles and the ball are kept inside it, apart from the ball leaving
+/// through the left or right edge to score.
+pub const COURT: Aabb = Aabb {
+    min: glm::Vec2::new(-1.0, -1.0),
+    max: glm::Vec2::new(1.0, 1.0),
+};
+
+/// Most bounces the ball can make in a single tick. Whatever time is left after that is dropped.
+pub const MAX_BOUNCES: usize = 4;
+
+/// How long the match counts down in `GameState::Starting` before the ball is served, in
+/// seconds.
+pub const SERVE_DELAY: f32 = 3.0;
+
+/// Radius of every ball.
+pub const BALL_RADIUS: f32 = 0.02;
+
+/// How long the match counts down in `GameState::Resuming` before play picks up after a pause,
+/// in seconds.
+pub const RESUME_COUNTDOWN: f32 = 3.0;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum GameState {
+    Starting,
+    Playing,
+    Pause,
+    Resuming, // counting down out of a pause
+    WinScreen,
+}
+
+/// What a paddle wants to do this tick. Produced by the paddle's `PaddleController` (keyboard,
+/// AI, ...) so the simulation never has to look at raw input.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum PaddleIntent {
+    #[default]
+    Idle,
+    Up,
+    Down,
+    Axis(f32), // move at this share of full speed, -1 (down) to 1 (up)
+}
+
+/// How the ball comes off a paddle.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BounceConfig {
+    pub english: f32,           // 0 is a plain mirror bounce, 1 aims purely by where the paddle was hit
+    pub max_angle: f32,         // steepest angle off a paddle face, in radians
+    pub velocity_transfer: f32, // share of the paddle's vertical velocity passed on to the ball
+    pub speedup: f32,           // speed added to the ball on every paddle hit
+    pub max_speed: f32,
+}
+
+impl Default for BounceConfig {
+    fn default() -> Self {
+        BounceConfig {
+            english: 1.0,
+            max_angle: 60.0f32.to_radians(),
+            velocity_transfer: 0.3,
+            speedup: 0.05,
+            max_speed: 3.0,
+        }
+    }
+}
+
+/// How the ball leaves the centre spot on a serve. Angle and speed are picked at random between
+/// the limits, the angle going up or down with even odds.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ServeConfig {
+    pub min_angle: f32, // flattest serve, in radians off the horizontal
+    pub max_angle: f32, // steepest serve, in radians off the horizontal
+    pub min_speed: f32,
+    pub max_speed: f32,
+}
+
+impl Default for ServeConfig {
+    fn default() -> Self {
+        ServeConfig {
+            min_angle: 5.0f32.to_radians(),
+            max_angle: 25.0f32.to_radians(),
+            min_speed: 0.9,
+            max_speed: 1.2,
+        }
+    }
+}
+
+/// Everything needed to set up a match.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct MatchConfig {
+    pub players: u32, // human players, filled in from the left paddle
+    pub rules: MatchRules,
+    pub bounce: BounceConfig,
+    pub serve: ServeConfig,
+    pub mode: GameMode,
+    pub power_ups: PowerUpConfig, // only used in `GameMode::Arcade`
+    pub difficulty: AiDifficulty, // for whichever paddles the computer plays
+    pub seed: u64,                // everything random in the match comes from this
+}
+
+impl MatchConfig {
+    pub fn new(players: u32, rules: MatchRules) -> Self {
+        MatchConfig {
+            players,
+            rules,
+            ..Default::default()
+        }
+    }
+}
+
+pub struct GameData {
+    state_time: f32, // seconds spent in the current state
+    state: GameState,
+    config: MatchConfig,
+    server: usize,          // index of the player serving the next point
+    winner: Option<usize>,  // index of the match winner once we reach `GameState::WinScreen`
+    rally: u32,             // paddle hits since the last serve
+    rng: Rng,               // picks the serves
+    events: Vec<MatchEvent>, // what happened since the events were last drained
+    balls: Vec<Ball>,        // never empty, the first one is the ball served
+    next_ball_id: u64,
+    power_ups: Vec<PowerUp>, // waiting on the court
+    effects: Vec<Effect>,    // timed power-ups at work
+    spawn_time: f32,         // seconds of play since the last power-up spawned
+    players: Vec<Player>,
+    surfaces: Vec<Surface>,
+}
+
+impl GameData {
+    pub fn new(config: MatchConfig) -> Self {
+        let players = config.players;
+        let mut next_item_id = 0; // this is so stupid lol
+                                  //
+        // Paddles.
+        let x_pos = 0.99;
+        let left_paddle = Paddle::new(
+            next_item_id,
+            glm::Vec2::new(-x_pos + (PADDLE_WIDTH / 2.0f32), 0.0),
+            glm::Vec2::new(0.0, 1.0),
+            PADDLE_WIDTH,
+            PADDLE_HEIGHT,
+        );
+        next_item_id += 1;
+        let right_paddle = Paddle::new(
+            next_item_id,
+            glm::Vec2::new(x_pos - (PADDLE_WIDTH / 2.0f32), 0.0),
+            glm::Vec2::new(0.0, 1.0),
+            PADDLE_WIDTH,
+            PADDLE_HEIGHT,
+        );
+        next_item_id += 1;
+
+        // Balls.
+        let ball = Ball::new(next_item_id, BALL_RADIUS);
+        next_item_id += 1;
+
+        // Extra surfaces
+        let [floor, _, ceiling, _] = COURT.edges();
+
+        // The left paddle is the first human player, the right paddle the second. Human paddles
+        // stand still until the shell gives them a controller with `set_controller`.
+        let controller = |id: u64, human: bool| -> Box<dyn PaddleController> {
+            if human {
+                Box::new(PaddleIntent::Idle)
+            } else {
+                Box::new(Ai::new(config.difficulty, config.seed.wrapping_add(id)))
+            }
+        };
+        let player1 = Player::new(0, left_paddle, controller(0, players >= 1));
+        let player2 = Player::new(1, right_paddle, controller(1, players >= 2));
+
+        let mut game = GameData {
+            state_time: 0.0,
+            state: GameState::Starting,
+            config,
+            server: 0,
+            winner: None,
+            rally: 0,
+            rng: Rng::new(config.seed.wrapping_add(ball.id())),
+            events: Vec::new(),
+            balls: vec![ball],
+            next_ball_id: next_item_id,
+            power_ups: Vec::new(),
+            effects: Vec::new(),
+            spawn_time: 0.0,
+            players: vec![player1, player2],
+            surfaces: vec![floor, ceiling],
+        };
+        game.reset();
+        game
+    }
+
+    // Sets up the next serve.
+    pub fn reset(&mut self) {
+        self.set_state(GameState::Starting);
+
+        let velocity = self.serve_velocity();
+        self.balls.truncate(1);
+        if self.balls.is_empty() {
+            self.balls.push(Ball::new(self.next_ball_id, BALL_RADIUS));
+            self.next_ball_id += 1;
+        }
+        self.balls[0].reset(velocity);
+        self.rally = 0;
+    }
+
+    // A random serve within `ServeConfig`'s limits. The left player serves towards the right and
+    // vice versa.
+    fn serve_velocity(&mut self) -> glm::Vec2 {
+        let serve = self.config.serve;
+        let direction = if self.server == 0 { 1.0f32 } else { -1.0f32 };
+        let mut angle = self.rng.range(serve.min_angle, serve.max_angle);
+        if self.rng.chance(0.5) {
+            angle = -angle;
+        }
+        let speed = self.rng.range(serve.min_speed, serve.max_speed);
+        glm::Vec2::new(angle.cos() * direction, angle.sin()) * speed
+    }
+
+    /// Starts the whole match over with the same config.
+    pub fn restart(&mut self) {
+        for player in &mut self.players {
+            player.reset();
+        }
+        self.server = 0;
+        self.winner = None;
+        self.power_ups.clear();
+        self.effects.clear();
+        self.spawn_time = 0.0;
+        self.resize_paddles();
+        self.reset();
+        self.events.push(MatchEvent::Restarted);
+    }
+
+    pub fn state(&self) -> GameState {
+        self.state
+    }
+
+    pub fn config(&self) -> &MatchConfig {
+        &self.config
+    }
+
+    pub fn rules(&self) -> &MatchRules {
+        &self.config.rules
+    }
+
+    pub fn server(&self) -> usize {
+        self.server
+    }
+
+    pub fn winner(&self) -> Option<usize> {
+        self.winner
+    }
+
+    pub fn scores(&self) -> [u32; 2] {
+        [self.players[0].score(), self.players[1].score()]
+    }
+
+    pub fn sets(&self) -> [u32; 2] {
+        [self.players[0].sets(), self.players[1].sets()]
+    }
+
+    pub fn rally(&self) -> u32 {
+        self.rally
+    }
+
+    /// The first ball in play. Outside `GameMode::Arcade` it's the only one.
+    pub fn ball(&self) -> &Ball {
+        &self.balls[0]
+    }
+
+    pub fn balls(&self) -> &[Ball] {
+        &self.balls
+    }
+
+    pub fn power_ups(&self) -> &[PowerUp] {
+        &self.power_ups
+    }
+
+    pub fn effects(&self) -> &[Effect] {
+        &self.effects
+    }
+
+    /// Whether a timed `kind` power-up is working on `player`'s side.
+    pub fn has_effect(&self, player: usize, kind: PowerUpKind) -> bool {
+        self.effects.iter().any(|effect| effect.player == player && effect.kind == kind)
+    }
+
+    pub fn players(&self) -> &[Player] {
+        &self.players
+    }
+
+    /// Hands player `player`'s paddle to `controller`, e.g. a keyboard, a bot or a network peer.
+    pub fn set_controller(&mut self, player: usize, controller: Box<dyn PaddleController>) {
+        self.players[player].set_controller(controller);
+    }
+
+    /// The match as player `player` sees it.
+    pub fn view(&self, player: usize) -> MatchView {
+        MatchView {
+            player,
+            state: self.state,
+            scores: self.scores(),
+            rally: self.rally,
+            ball: self.incoming_ball(player),
+            paddle: self.players[player].paddle,
+            opponent: self.players[1 - player].paddle,
+        }
+    }
+
+    pub fn pause(&mut self) {
+        if let GameState::Playing | GameState::Resuming = self.state {
+            self.set_state(GameState::Pause);
+        }
+    }
+
+    /// Starts the countdown out of a pause. Play picks up `RESUME_COUNTDOWN` seconds later.
+    pub fn unpause(&mut self) {
+        if let GameState::Pause = self.state {
+            self.set_state(GameState::Resuming);
+        }
+    }
+
+    /// Cuts the countdown to a serve short if `player` is the one serving. The ball goes on the
+    /// next tick.
+    pub fn serve_now(&mut self, player: usize) {
+        if self.state == GameState::Starting && player == self.server {
+            self.state_time = self.state_time.max(SERVE_DELAY);
+        }
+    }
+
+    /// Seconds left before the ball moves, while counting down to a serve or out of a pause.
+    pub fn countdown(&self) -> Option<f32> {
+        match self.state {
+            GameState::Starting => Some((SERVE_DELAY - self.state_time).max(0.0f32)),
+            GameState::Resuming => Some((RESUME_COUNTDOWN - self.state_time).max(0.0f32)),
+            _ => None,
+        }
+    }
+
+    // The ball `player` most needs to worry about: the first to reach their paddle, or the first
+    // ball if none is heading their way.
+    fn incoming_ball(&self, player: usize) -> Ball {
+        let x = self.players[player].paddle.position.x;
+        let arrival = |ball: &&Ball| (x - ball.position.x) / ball.velocity.x;
+        self.balls.iter()
+            .filter(|ball| ball.velocity.x != 0.0f32 && ball.velocity.x.signum() == x.signum())
+            .min_by(|a, b| arrival(a).total_cmp(&arrival(b)))
+            .copied()
+            .unwrap_or(self.balls[0])
+    }
+
+    /// Hands over everything that happened since the last call, oldest first.
+    pub fn drain_events(&mut self) -> Vec<MatchEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    fn set_state(&mut self, state: GameState) {
+        if state != self.state {
+            self.events.push(MatchEvent::StateChanged { from: self.state, to: state });
+        }
+        self.state = state;
+        self.state_time = 0.0;
+    }
+
+    /// Advances the match by one tick of `delta` seconds. Meant to be driven with a fixed
+    /// `delta` (see `core::timestep::FixedTimestep`) so the physics doesn't depend on frame rate.
+    pub fn update(&mut self, delta: f32) {
+        for ball in &mut self.balls {
+            ball.store_previous();
+        }
+        for player in &mut self.players {
+            player.paddle.store_previous();
+        }
+
+        match &self.state {
+            GameState::Starting => {
+                self.state_time += delta;
+                if self.state_time >= SERVE_DELAY {
+                    self.set_state(GameState::Playing);
+                    self.events.push(MatchEvent::Served {
+                        server: self.server,
+                        receiver: 1 - self.server,
+                        speed: self.balls[0].velocity.norm(),
+                    });
+                }
+            },
+            GameState::Playing => {
+                self.state_time += delta;
+                for i in 0..self.balls.len() {
+                    self.step_ball(i, delta);
+                    let ball = &mut self.balls[i];
+                    ball.clamp_velocity(self.config.bounce.max_speed * ball.boost);
+                }
+                if self.config.mode == GameMode::Arcade {
+                    self.update_power_ups(delta);
+                }
+
+                // Every ball out is a point. The rally goes on while there are balls left.
+                let mut scorers = Vec::new();
+                self.balls.retain(|ball| {
+                    if ball.position.x > 1.0f32 {
+                        scorers.push(0);
+                    } else if ball.position.x < -1.0f32 {
+                        scorers.push(1);
+                    }
+                    ball.position.x.abs() <= 1.0f32
+                });
+                // All of them count, even if an earlier one ends the set.
+                let mut set_over = false;
+                for scorer in scorers {
+                    set_over |= self.score_point(scorer);
+                    if self.state == GameState::WinScreen {
+                        return;
+                    }
+                }
+                if set_over || self.balls.is_empty() {
+                    self.reset();
+                    return;
+                }
+
+                for i in 0..self.players.len() {
+                    let view = self.view(i);
+                    let player = &mut self.players[i];
+                    player.update(delta, &view);
+                    player.paddle.update_motion(delta);
+                }
+            },
+            GameState::Resuming => {
+                self.state_time += delta;
+                if self.state_time >= RESUME_COUNTDOWN {
+                    self.set_state(GameState::Playing);
+                }
+            },
+            GameState::Pause => { },
+            GameState::WinScreen => { },
+        };
+    }
+}
+
+impl GameData {
+    // Moves ball `index` through `delta` seconds. Rather than testing where the ball ends up we
+    // sweep it along its path, stop at the first thing it touches, bounce, and carry on with
+    // whatever time is left, so a fast ball or a long tick can't skip through a paddle.
+    fn step_ball(&mut self, index: usize, delta: f32) {
+        if self.hold_ball(index, delta) {
+            return;
+        }
+        self.separate_ball(index);
+
+        let shields = [(0, COURT.edges()[3]), (1, COURT.edges()[1])];
+        let mut remaining = delta;
+        for _ in 0..MAX_BOUNCES {
+            let ball = &self.balls[index];
+            if ball.hold.is_some() {
+                return;
+            }
+            let displacement = ball.velocity * remaining;
+            let mut first: Option<(Hit, Touch)> = None;
+
+            let paddles = self.players.iter().enumerate()
+                .flat_map(|(i, player)| player.paddle.surfaces().map(|surface| (surface, Touch::Paddle(i))));
+            let walls = self.surfaces.iter()
+                .map(|surface| (*surface, Touch::Wall));
+            let shields = shields.iter()
+                .filter(|(player, _)| self.has_effect(*player, PowerUpKind::Shield))
+                .map(|(player, surface)| (*surface, Touch::Shield(*player)));
+
+            for (surface, touch) in paddles.chain(walls).chain(shields) {
+                let hit = sweep_circle_segment(&ball.position, ball.radius, &displacement, &surface);
+                if let Some(hit) = hit
+                    && first.is_none_or(|(earliest, _)| hit.toi < earliest.toi) {
+                    first = Some((hit, touch));
+                }
+            }
+
+            let Some((hit, touch)) = first else {
+                self.balls[index].apply_velocity(remaining);
+                return;
+            };
+            let ball = &mut self.balls[index];
+            ball.apply_velocity(remaining * hit.toi);
+            let bounce = &self.config.bounce;
+            match touch {
+                Touch::Paddle(i) => {
+                    let paddle = &self.players[i].paddle;
+                    resolve_collision(ball, &hit, bounce.english, Some(paddle), bounce);
+                    self.hit_paddle(index, i, hit.point.y);
+                },
+                Touch::Wall => {
+                    resolve_collision(ball, &hit, 0.0f32, None, bounce);
+                    self.events.push(MatchEvent::BallHitWall { speed: ball.velocity.norm(), contact_x: hit.point.x });
+                },
+                Touch::Shield(player) => {
+                    resolve_collision(ball, &hit, 0.0f32, None, bounce);
+                    self.effects.retain(|effect| !(effect.player == player && effect.kind == PowerUpKind::Shield));
+                    self.events.push(MatchEvent::ShieldBlocked { player });
+                },
+            }
+            remaining *= 1.0f32 - hit.toi;
+        }
+    }
+
+    // Carries ball `index` along with the sticky paddle holding it. Returns whether it was held
+    // this tick; once its time is up it leaves with the velocity it bounced off with.
+    fn hold_ball(&mut self, index: usize, delta: f32) -> bool {
+        let ball = &mut self.balls[index];
+        let Some(hold) = &mut ball.hold else {
+            return false;
+        };
+        ball.position = self.players[hold.player].paddle.position + hold.offset;
+        hold.time_left -= delta;
+        if hold.time_left <= 0.0f32 {
+            ball.hold = None;
+        }
+        true
+    }
+
+    // Pushes ball `index` out of any paddle it has ended up inside, which happens when a paddle
+    // moves onto the ball. Sweeping only works for shapes that start apart.
+    fn separate_ball(&mut self, index: usize) {
+        for i in 0..self.players.len() {
+            let ball = &mut self.balls[index];
+            let paddle = &self.players[i].paddle;
+            let Some(contact) = contact_circle_aabb(&ball.shape(), &paddle.bounds()) else {
+                continue;
+            };
+            if contact.depth <= 0.0f32 {
+                continue;
+            }
+
+            ball.position += contact.normal * contact.depth;
+            let r = ball.radius;
+            ball.position.y = ball.position.y.clamp(COURT.min.y + r, COURT.max.y - r);
+
+            if ball.velocity.dot(&contact.normal) < 0.0f32 {
+                let hit = Hit { toi: 0.0, normal: contact.normal, point: contact.point };
+                let bounce = &self.config.bounce;
+                resolve_collision(ball, &hit, bounce.english, Some(paddle), bounce);
+                self.hit_paddle(index, i, contact.point.y);
+            }
+        }
+    }
+
+    // Counts a paddle hit once ball `index` has bounced off `player`'s paddle, and applies
+    // whatever power-ups that paddle has.
+    fn hit_paddle(&mut self, index: usize, player: usize, contact_y: f32) {
+        self.rally += 1;
+        let boosted = self.has_effect(player, PowerUpKind::SpeedBoost);
+        let sticky = self.has_effect(player, PowerUpKind::Sticky);
+        let paddle = self.players[player].paddle.position;
+        let ball = &mut self.balls[index];
+        ball.last_hit = Some(player);
+        ball.boost = if boosted { SPEED_BOOST } else { 1.0f32 };
+        if boosted {
+            ball.velocity *= SPEED_BOOST;
+        }
+        if sticky {
+            ball.hold = Some(Hold { player, offset: ball.position - paddle, time_left: STICKY_HOLD });
+        }
+        self.events.push(MatchEvent::BallHitPaddle { player, speed: ball.velocity.norm(), contact_y });
+    }
+
+    // Arcade mode: runs down the timers, hands out power-ups balls ran into and spawns new ones.
+    fn update_power_ups(&mut self, delta: f32) {
+        let mut expired = Vec::new();
+        self.effects.retain_mut(|effect| {
+            effect.time_left -= delta;
+            if effect.time_left <= 0.0f32 {
+                expired.push(*effect);
+            }
+            effect.time_left > 0.0f32
+        });
+        for effect in expired {
+            self.events.push(MatchEvent::PowerUpExpired { player: effect.player, kind: effect.kind });
+        }
+        self.power_ups.retain_mut(|power_up| {
+            power_up.time_left -= delta;
+            power_up.time_left > 0.0f32
+        });
+
+        for i in 0..self.balls.len() {
+            let ball = self.balls[i];
+            let Some(player) = ball.last_hit else {
+                continue;
+            };
+            if let Some(found) = self.power_ups.iter().position(|power_up| power_up.shape().overlaps_circle(&ball.shape())) {
+                let power_up = self.power_ups.remove(found);
+                self.collect(player, power_up.kind, i);
+            }
+        }
+
+        let config = self.config.power_ups;
+        self.spawn_time += delta;
+        if self.spawn_time >= config.spawn_interval {
+            self.spawn_time = 0.0;
+            if self.power_ups.len() < config.max_on_court {
+                let kind = PowerUpKind::ALL[self.rng.next_u32() as usize % PowerUpKind::ALL.len()];
+                let position = glm::Vec2::new(self.rng.range(-0.5, 0.5), self.rng.range(-0.7, 0.7));
+                self.power_ups.push(PowerUp { kind, position, time_left: config.lifetime });
+                self.events.push(MatchEvent::PowerUpSpawned { kind, position });
+            }
+        }
+        self.resize_paddles();
+    }
+
+    // `player` ran ball `index` into a `kind` power-up. Timed ones start over if they were
+    // already at work rather than stacking.
+    fn collect(&mut self, player: usize, kind: PowerUpKind, index: usize) {
+        self.events.push(MatchEvent::PowerUpCollected { player, kind });
+        if kind == PowerUpKind::Split {
+            if self.balls.len() < self.config.power_ups.max_balls {
+                let mut ball = self.balls[index];
+                ball.id = self.next_ball_id;
+                self.next_ball_id += 1;
+                // Off the other way vertically, or a little up if it was going flat.
+                ball.velocity.y = if ball.velocity.y.abs() > 0.1f32 {
+                    -ball.velocity.y
+                } else {
+                    ball.velocity.norm() * 0.3f32
+                };
+                ball.hold = None;
+                self.balls.push(ball);
+            }
+            return;
+        }
+
+        let target = if kind.hits_opponent() { 1 - player } else { player };
+        let duration = self.config.power_ups.duration;
+        match self.effects.iter_mut().find(|effect| effect.player == target && effect.kind == kind) {
+            Some(effect) => effect.time_left = duration,
+            None => self.effects.push(Effect { player: target, kind, time_left: duration }),
+        }
+    }
+
+    // Sets each paddle's height from the grow and shrink power-ups working on it.
+    fn resize_paddles(&mut self) {
+        for i in 0..self.players.len() {
+            let mut height = PADDLE_HEIGHT;
+            if self.has_effect(i, PowerUpKind::Grow) {
+                height *= GROW_FACTOR;
+            }
+            if self.has_effect(i, PowerUpKind::Shrink) {
+                height *= SHRINK_FACTOR;
+            }
+            let paddle = &mut self.players[i].paddle;
+            if paddle.height != height {
+                paddle.height = height;
+                paddle.move_y(paddle.position.y);
+            }
+        }
+    }
+
+    // Awards a point to `scorer` and works out whether that finished the set or the match. The
+    // next serve waits until the set is over or the last ball is out.
+    // Gives `scorer` a point and moves the serve on. Returns whether that ended the set; resetting
+    // for the next serve is left to the caller, since more balls may go out in the same tick.
+    fn score_point(&mut self, scorer: usize) -> bool {
+        let rules = self.config.rules;
+        self.players[scorer].increment_score();
+        self.events.push(MatchEvent::PointScored { scorer, scores: self.scores() });
+
+        let mut set_over = false;
+        if let Some(set_winner) = rules.set_winner(self.scores()) {
+            self.players[set_winner].increment_sets();
+            self.events.push(MatchEvent::SetWon { winner: set_winner, sets: self.sets() });
+
+            if let Some(match_winner) = rules.match_winner(self.sets()) {
+                self.winner = Some(match_winner);
+                self.events.push(MatchEvent::MatchWon { winner: match_winner, sets: self.sets() });
+                self.balls.truncate(1);
+                if self.balls.is_empty() {
+                    self.balls.push(Ball::new(self.next_ball_id, BALL_RADIUS));
+                    self.next_ball_id += 1;
+                }
+                self.balls[0].reset(glm::Vec2::zeros());
+                self.set_state(GameState::WinScreen);
+                return true;
+            }
+
+            for player in &mut self.players {
+                player.reset_score();
+            }
+            if rules.serve_rotation == ServeRotation::EverySet {
+                self.server = 1 - self.server;
+            }
+            set_over = true;
+        }
+
+        match rules.serve_rotation {
+            ServeRotation::ToLoser => self.server = scorer,
+            ServeRotation::EveryPoint => self.server = 1 - self.server,
+            ServeRotation::EverySet => { },
+        }
+        set_over
+    }
+}
+
+// What a ball can run into.
+#[derive(Clone, Copy)]
+enum Touch {
+    Paddle(usize),
+    Wall,
+    Shield(usize), // the player it guards
+}
+
+// Bounces the ball off whatever it hit. Off a paddle the ball also picks up english (see
+// `calculate_bounce_velocity`), part of the paddle's vertical velocity and a little extra speed.
+fn resolve_collision(ball: &mut Ball, hit: &Hit, factor: f32, paddle: Option<&Paddle>, bounce: &BounceConfig) {
+    let Some(paddle) = paddle else {
+        ball.velocity = calculate_bounce_velocity(&hit.normal, ball.velocity, factor, 0.0f32, bounce.max_angle);
+        return;
+    };
+
+    let speed = ball.velocity.norm() + bounce.speedup;
+    let mut velocity;
+    if hit.normal.x.abs() >= hit.normal.y.abs() {
+        // Front or back face, or the face side of a corner: aim by where it hit, as if it had hit
+        // the face itself.
+        let face = glm::Vec2::new(hit.normal.x.signum(), 0.0f32);
+        let offset = paddle.hit_offset(&hit.point);
+        velocity = calculate_bounce_velocity(&face, ball.velocity, factor, offset, bounce.max_angle);
+        velocity.y += paddle.motion().y * bounce.velocity_transfer;
+        velocity = limit_angle(velocity, &face, bounce.max_angle);
+    } else {
+        // Top or bottom: there's nothing to aim with, so mirror it. A paddle moving into the ball
+        // mustn't be faster than the ball leaving it or it would catch up and hit it again.
+        velocity = calculate_bounce_velocity(&hit.normal, ball.velocity, 0.0f32, 0.0f32, bounce.max_angle);
+        let push = paddle.motion().dot(&hit.normal);
+        let away = velocity.dot(&hit.normal);
+        if push > away {
+            velocity += hit.normal * (push - away);
+        }
+    }
+
+    ball.velocity = velocity.try_normalize(f32::EPSILON)
+        .map_or(velocity, |direction| direction * speed.max(velocity.norm()).min(bounce.max_speed));
+}
+
+// Reflects `velocity` about `normal`. `factor` blends the mirror bounce with a classic Pong bounce
+// whose angle only depends on `offset`, where the ball hit along the surface (-1 at the bottom,
+// 1 at the top): hitting dead centre sends the ball straight back, hitting an end sends it off at
+// `max_angle`. A factor of 0 is a plain mirror bounce. Speed is kept either way.
+fn calculate_bounce_velocity(normal: &glm::Vec2, velocity: glm::Vec2, factor: f32, offset: f32, max_angle: f32) -> glm::Vec2 {
+    let dot = normal.dot(&velocity);
+    let vx = velocity.x - 2.0f32 * dot * normal.x;
+    let vy = velocity.y - 2.0f32 * dot * normal.y;
+    let mirrored = glm::Vec2::new(vx, vy);
+
+    let factor = factor.clamp(0.0f32, 1.0f32);
+    let speed = velocity.norm();
+    if factor == 0.0f32 || speed == 0.0f32 {
+        return mirrored;
+    }
+
+    let angle = offset.clamp(-1.0f32, 1.0f32) * max_angle;
+    let up = glm::Vec2::new(-normal.y, normal.x) * normal.x.signum();
+    let aimed = normal * angle.cos() + up * angle.sin();
+
+    let direction = glm::lerp(&(mirrored / speed), &aimed, factor)
+        .try_normalize(f32::EPSILON)
+        .unwrap_or(aimed);
+    direction * speed
+}
+
+// Keeps `velocity` within `max_angle` of `normal` so the ball can't leave a paddle (nearly)
+// parallel to it.
+fn limit_angle(velocity: glm::Vec2, normal: &glm::Vec2, max_angle: f32) -> glm::Vec2 {
+    let speed = velocity.norm();
+    let along = velocity.dot(normal);
+    let tangent = glm::Vec2::new(-normal.y, normal.x);
+    let across = velocity.dot(&tangent);
+
+    let angle = across.atan2(along.max(0.0f32)).clamp(-max_angle, max_angle);
+    (normal * angle.cos() + tangent * angle.sin()) * speed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DT: f32 = 1.0 / 120.0;
+
+    #[test]
+    fn serves_after_delay() {
+        let mut game = GameData::new(MatchConfig::default());
+        while game.state_time + DT < SERVE_DELAY {
+            game.update(DT);
+            assert_eq!(GameState::Starting, game.state());
+        }
+        game.update(DT);
+        game.update(DT);
+        assert_eq!(GameState::Playing, game.state());
+    }
+
+    #[test]
+    fn server_can_serve_early() {
+        let mut game = GameData::new(MatchConfig::default());
+        game.update(DT);
+        game.serve_now(1 - game.server());
+        game.update(DT);
+        assert_eq!(GameState::Starting, game.state());
+
+        game.serve_now(game.server());
+        game.update(DT);
+        assert_eq!(GameState::Playing, game.state());
+    }
+
+    #[test]
+    fn resumes_after_countdown() {
+        let mut game = GameData::new(MatchConfig::default());
+        game.set_state(GameState::Playing);
+        game.pause();
+        let ball = *game.ball().position();
+        game.update(DT);
+        assert_eq!(GameState::Pause, game.state());
+
+        game.unpause();
+        assert_eq!(Some(RESUME_COUNTDOWN), game.countdown());
+        while game.countdown().is_some_and(|left| left > DT) {
+            game.update(DT);
+        }
+        assert_eq!(ball, *game.ball().position());
+        game.update(DT);
+        game.update(DT);
+        assert_eq!(GameState::Playing, game.state());
+        assert_eq!(None, game.countdown());
+    }
+
+    #[test]
+    fn paddle_follows_its_controller() {
+        let mut game = GameData::new(MatchConfig::new(2, MatchRules::default()));
+        assert!(!game.players()[0].is_computer());
+        game.set_controller(0, Box::new(ScriptedController::new(vec![PaddleIntent::Up])));
+        game.set_state(GameState::Playing);
+        let start = game.players()[0].paddle().position().y;
+        game.update(DT);
+        assert!(game.players()[0].paddle().position().y > start);
+        assert_eq!(0.0, game.players()[1].paddle().position().y);
+    }
+
+    #[test]
+    fn interpolates_between_ticks() {
+        let mut game = GameData::new(MatchConfig::default());
+        game.set_state(GameState::Playing);
+        let before = *game.ball().position();
+        game.update(DT);
+        let ball = game.ball();
+        let halfway = ball.interpolated_position(0.5);
+        let expected = (before + ball.position) * 0.5;
+        assert!(glm::distance(&halfway, &expected) < 1e-6);
+        assert_eq!(ball.position, ball.interpolated_position(1.0));
+    }
+
+    // Knocks the ball out past `side`'s edge and lets the sim notice.
+    fn concede(game: &mut GameData, side: usize) {
+        game.set_state(GameState::Playing);
+        game.balls[0].position.x = if side == 0 { -1.5 } else { 1.5 };
+        game.update(DT);
+    }
+
+    #[test]
+    fn points_go_to_the_right_player() {
+        let rules = MatchRules::default().serve_rotation(ServeRotation::EveryPoint);
+        let mut game = GameData::new(MatchConfig::new(0, rules));
+        concede(&mut game, 1);
+        assert_eq!([1, 0], game.scores());
+        assert_eq!(GameState::Starting, game.state());
+        assert_eq!(1, game.server());
+        assert!(game.ball().velocity().x < 0.0);
+
+        concede(&mut game, 0);
+        assert_eq!([1, 1], game.scores());
+        assert_eq!(0, game.server());
+        assert!(game.ball().velocity().x > 0.0);
+    }
+
+    #[test]
+    fn serves_go_to_the_loser_within_limits() {
+        let config = MatchConfig { seed: 9, ..Default::default() };
+        let serve = config.serve;
+        let mut game = GameData::new(config);
+        assert_eq!(Some(SERVE_DELAY), game.countdown());
+
+        let mut angles = Vec::new();
+        for point in 0..10 {
+            let loser = if point % 3 == 0 { 0 } else { 1 };
+            concede(&mut game, loser);
+            assert_eq!(1 - loser, game.server());
+            let velocity = *game.ball().velocity();
+            assert_eq!(loser == 1, velocity.x > 0.0);
+            let speed = velocity.norm();
+            assert!(speed >= serve.min_speed - 1e-5 && speed <= serve.max_speed + 1e-5);
+            let angle = (velocity.y / velocity.x).abs().atan();
+            assert!(angle >= serve.min_angle - 1e-5 && angle <= serve.max_angle + 1e-5);
+            angles.push(velocity.y);
+        }
+        assert!(angles.iter().any(|y| *y > 0.0) && angles.iter().any(|y| *y < 0.0));
+
+        // The same seed serves the same way.
+        let mut again = GameData::new(config);
+        concede(&mut again, 0);
+        let mut first = GameData::new(config);
+        concede(&mut first, 0);
+        assert_eq!(again.ball().velocity(), first.ball().velocity());
+    }
+
+    #[test]
+    fn reports_what_happened() {
+        let mut game = GameData::new(MatchConfig::new(2, MatchRules::first_to(1, 1)));
+        while game.state() == GameState::Starting {
+            game.update(DT);
+        }
+        let events = game.drain_events();
+        assert_eq!(MatchEvent::StateChanged { from: GameState::Starting, to: GameState::Playing }, events[0]);
+        assert!(matches!(events[1], MatchEvent::Served { server: 0, receiver: 1, .. }));
+
+        launch(&mut game, glm::Vec2::new(0.8, 0.05), glm::Vec2::new(2.0, 0.0));
+        game.drain_events();
+        game.update(0.1);
+        let events = game.drain_events();
+        let [MatchEvent::BallHitPaddle { player: 1, speed, contact_y }] = events[..] else {
+            panic!("expected a paddle hit, got {:?}", events);
+        };
+        assert!((speed - 2.0 - game.config().bounce.speedup).abs() < 1e-5);
+        assert!((contact_y - 0.05).abs() < 0.01);
+
+        concede(&mut game, 1);
+        assert_eq!(vec![
+            MatchEvent::PointScored { scorer: 0, scores: [1, 0] },
+            MatchEvent::SetWon { winner: 0, sets: [1, 0] },
+            MatchEvent::MatchWon { winner: 0, sets: [1, 0] },
+            MatchEvent::StateChanged { from: GameState::Playing, to: GameState::WinScreen },
+        ], game.drain_events());
+        assert!(game.drain_events().is_empty());
+    }
+
+    fn arcade() -> GameData {
+        let config = MatchConfig { mode: GameMode::Arcade, ..MatchConfig::new(2, MatchRules::default()) };
+        let mut game = GameData::new(config);
+        game.set_state(GameState::Playing);
+        game
+    }
+
+    // Drops a `kind` power-up in front of the ball, as if `player` had just hit it.
+    fn collect(game: &mut GameData, player: usize, kind: PowerUpKind) {
+        game.balls[0].position = glm::Vec2::new(-0.1, 0.0);
+        game.balls[0].velocity = glm::Vec2::new(1.0, 0.0);
+        game.balls[0].last_hit = Some(player);
+        game.power_ups.push(PowerUp { kind, position: glm::Vec2::new(0.0, 0.0), time_left: 5.0 });
+        while !game.power_ups.is_empty() {
+            game.update(DT);
+        }
+    }
+
+    #[test]
+    fn power_ups_spawn_and_run_out() {
+        let mut game = arcade();
+        let config = game.config().power_ups;
+        game.balls[0].velocity = glm::Vec2::new(0.0, 0.5);
+        let mut time = 0.0;
+        while time < config.spawn_interval + DT {
+            game.update(DT);
+            time += DT;
+        }
+        assert_eq!(1, game.power_ups().len());
+        assert!(game.drain_events().iter().any(|event| matches!(event, MatchEvent::PowerUpSpawned { .. })));
+
+        game.power_ups.clear();
+        collect(&mut game, 0, PowerUpKind::Grow);
+        collect(&mut game, 0, PowerUpKind::Shrink);
+        assert!(game.drain_events().contains(&MatchEvent::PowerUpCollected { player: 0, kind: PowerUpKind::Grow }));
+        assert!((game.players()[0].paddle().height() - PADDLE_HEIGHT * GROW_FACTOR).abs() < 1e-6);
+        assert!((game.players()[1].paddle().height() - PADDLE_HEIGHT * SHRINK_FACTOR).abs() < 1e-6);
+
+        game.balls[0].velocity = glm::Vec2::new(0.0, 0.5);
+        while !game.effects().is_empty() {
+            game.update(DT);
+        }
+        assert!(game.drain_events().contains(&MatchEvent::PowerUpExpired { player: 0, kind: PowerUpKind::Grow }));
+        assert_eq!(PADDLE_HEIGHT, game.players()[0].paddle().height());
+        assert_eq!(PADDLE_HEIGHT, game.players()[1].paddle().height());
+    }
+
+    #[test]
+    fn split_balls_each_score() {
+        let mut game = arcade();
+        collect(&mut game, 0, PowerUpKind::Split);
+        assert_eq!(2, game.balls().len());
+        assert_ne!(game.balls()[0].id(), game.balls()[1].id());
+        assert_ne!(game.balls()[0].velocity().y, game.balls()[1].velocity().y);
+
+        game.balls[1].position.x = 1.5;
+        game.update(DT);
+        assert_eq!([1, 0], game.scores());
+        assert_eq!(GameState::Playing, game.state());
+        assert_eq!(1, game.balls().len());
+
+        concede(&mut game, 1);
+        assert_eq!([2, 0], game.scores());
+        assert_eq!(GameState::Starting, game.state());
+        assert_eq!(1, game.balls().len());
+    }
+
+    #[test]
+    fn balls_out_on_both_sides_in_one_tick_both_score() {
+        let mut game = arcade();
+        collect(&mut game, 0, PowerUpKind::Split);
+        assert_eq!(2, game.balls().len());
+
+        game.balls[0].position.x = 1.5;
+        game.balls[1].position.x = -1.5;
+        game.update(DT);
+        assert_eq!([1, 1], game.scores());
+        assert_eq!(GameState::Starting, game.state());
+        assert_eq!(1, game.balls().len());
+    }
+
+    #[test]
+    fn shield_saves_one_point() {
+        let mut game = arcade();
+        game.effects.push(Effect { player: 0, kind: PowerUpKind::Shield, time_left: 5.0 });
+        launch(&mut game, glm::Vec2::new(-0.9, 0.8), glm::Vec2::new(-2.0, 0.0));
+        game.update(0.1);
+        assert!(game.ball().velocity().x > 0.0);
+        assert!(game.drain_events().contains(&MatchEvent::ShieldBlocked { player: 0 }));
+        assert!(game.effects().is_empty());
+
+        launch(&mut game, glm::Vec2::new(-0.9, 0.8), glm::Vec2::new(-2.0, 0.0));
+        game.update(0.1);
+        assert_eq!([0, 1], game.scores());
+    }
+
+    #[test]
+    fn sticky_paddle_holds_then_boost_sends_it_off() {
+        let mut game = arcade();
+        game.effects.push(Effect { player: 1, kind: PowerUpKind::Sticky, time_left: 5.0 });
+        game.effects.push(Effect { player: 1, kind: PowerUpKind::SpeedBoost, time_left: 5.0 });
+        launch(&mut game, glm::Vec2::new(0.8, 0.0), glm::Vec2::new(1.0, 0.0));
+        while !game.ball().is_held() {
+            game.update(DT);
+        }
+        let held = *game.ball().position();
+        game.update(DT);
+        assert_eq!(held, *game.ball().position());
+        assert_eq!(Some(1), game.ball().last_hit());
+
+        let mut time = 0.0;
+        while game.ball().is_held() {
+            game.update(DT);
+            time += DT;
+        }
+        assert!(time <= STICKY_HOLD + DT);
+        let speed = game.ball().velocity().norm();
+        assert!(game.ball().velocity().x < 0.0);
+        assert!((speed - (1.0 + game.config().bounce.speedup) * SPEED_BOOST).abs() < 1e-4);
+        game.update(DT);
+        assert!(game.ball().position().x < held.x);
+    }
+
+    #[test]
+    fn match_ends_on_win_screen() {
+        let rules = MatchRules::first_to(2, 1).best_of(3).serve_rotation(ServeRotation::EverySet);
+        let mut game = GameData::new(MatchConfig::new(0, rules));
+        concede(&mut game, 0);
+        assert_eq!(0, game.server());
+        concede(&mut game, 0);
+        assert_eq!([0, 1], game.sets());
+        assert_eq!([0, 0], game.scores());
+        assert_eq!(1, game.server());
+
+        concede(&mut game, 0);
+        concede(&mut game, 0);
+        assert_eq!(GameState::WinScreen, game.state());
+        assert_eq!(Some(1), game.winner());
+
+        // Nothing moves until the match is restarted.
+        game.update(DT);
+        assert_eq!(GameState::WinScreen, game.state());
+        game.restart();
+        assert_eq!(GameState::Starting, game.state());
+        assert_eq!([0, 0], game.sets());
+        assert_eq!(None, game.winner());
+    }
+
+    // Puts the ball in play at `position` with `velocity`.
+    fn launch(game: &mut GameData, position: glm::Vec2, velocity: glm::Vec2) {
+        game.set_state(GameState::Playing);
+        game.balls[0].position = position;
+        game.balls[0].velocity = velocity;
+    }
+
+    #[test]
+    fn fast_ball_does_not_tunnel_through_paddle() {
+        let mut game = GameData::new(MatchConfig::new(2, MatchRules::default()));
+        // Would end the tick 0.7 past the paddle without a swept test.
+        launch(&mut game, glm::Vec2::new(0.5, 0.0), glm::Vec2::new(144.0, 0.0));
+        game.update(DT);
+        assert_eq!([0, 0], game.scores());
+        assert!(game.ball().velocity().x < 0.0);
+        let face = game.players()[1].paddle().position().x - PADDLE_WIDTH / 2.0;
+        assert!(game.ball().position().x < face);
+    }
+
+    #[test]
+    fn long_tick_does_not_tunnel() {
+        let mut game = GameData::new(MatchConfig::new(2, MatchRules::default()));
+        launch(&mut game, glm::Vec2::new(-0.2, 0.0), glm::Vec2::new(-2.0, 0.0));
+        game.update(0.75);
+        assert_eq!([0, 0], game.scores());
+        assert!(game.ball().velocity().x > 0.0);
+        // Used the time left over after the bounce.
+        assert!(game.ball().position().x > -0.9);
+    }
+
+    #[test]
+    fn several_bounces_in_one_tick() {
+        let mut config = MatchConfig::new(2, MatchRules::default());
+        config.bounce.english = 0.0;
+        config.bounce.max_speed = 20.0;
+        let mut game = GameData::new(config);
+        // Heads into the top right corner: off the ceiling and then the paddle face.
+        game.players[1].paddle.move_y(0.9);
+        launch(&mut game, glm::Vec2::new(0.9, 0.9), glm::Vec2::new(10.0, 10.0));
+        game.update(DT);
+        let velocity = game.ball().velocity();
+        assert!(velocity.x < 0.0 && velocity.y < 0.0);
+        assert_eq!([0, 0], game.scores());
+    }
+
+    // Sends the ball flat into the right paddle, `offset` paddle half-heights from its centre.
+    fn hit_right_paddle(game: &mut GameData, offset: f32) -> glm::Vec2 {
+        let paddle_y = game.players[1].paddle.position.y;
+        let y = paddle_y + offset * PADDLE_HEIGHT / 2.0;
+        launch(game, glm::Vec2::new(0.9, y), glm::Vec2::new(1.0, 0.0));
+        game.update(0.1);
+        *game.ball().velocity()
+    }
+
+    #[test]
+    fn bounce_angle_follows_hit_position() {
+        let mut game = GameData::new(MatchConfig::new(2, MatchRules::default()));
+        let centre = hit_right_paddle(&mut game, 0.0);
+        assert!(centre.x < 0.0 && centre.y.abs() < 1e-4);
+
+        let top = hit_right_paddle(&mut game, 0.9);
+        assert!(top.y > 0.0);
+        let bottom = hit_right_paddle(&mut game, -0.9);
+        assert!(bottom.y < 0.0);
+
+        let max_angle = game.config().bounce.max_angle;
+        let edge = hit_right_paddle(&mut game, 1.0);
+        assert!((edge.y.atan2(-edge.x) - max_angle).abs() < 1e-3);
+    }
+
+    #[test]
+    fn no_english_is_a_mirror_bounce() {
+        let mut config = MatchConfig::new(2, MatchRules::default());
+        config.bounce.english = 0.0;
+        config.bounce.speedup = 0.0;
+        let mut game = GameData::new(config);
+        launch(&mut game, glm::Vec2::new(0.9, 0.05), glm::Vec2::new(1.0, 0.2));
+        game.update(0.1);
+        let velocity = game.ball().velocity();
+        assert!((velocity.x + 1.0).abs() < 1e-4 && (velocity.y - 0.2).abs() < 1e-4);
+    }
+
+    #[test]
+    fn moving_paddle_drags_the_ball() {
+        let mut config = MatchConfig::new(2, MatchRules::default());
+        config.bounce.english = 0.0;
+        let mut game = GameData::new(config);
+        game.set_controller(1, Box::new(ScriptedController::new(vec![PaddleIntent::Up])));
+        game.set_state(GameState::Playing);
+        game.update(DT);
+        assert!(game.players()[1].paddle().motion().y > 0.0);
+
+        let y = game.players[1].paddle.position.y;
+        launch(&mut game, glm::Vec2::new(0.9, y), glm::Vec2::new(1.0, 0.0));
+        game.update(0.1);
+        assert!(game.ball().velocity().y > 0.0);
+    }
+
+    #[test]
+    fn rally_hits_speed_up_the_ball() {
+        let mut game = GameData::new(MatchConfig::new(2, MatchRules::default()));
+        let bounce = game.config().bounce;
+        let first = hit_right_paddle(&mut game, 0.0).norm();
+        assert!((first - (1.0 + bounce.speedup)).abs() < 1e-4);
+        assert_eq!(1, game.rally());
+
+        launch(&mut game, glm::Vec2::new(0.9, 0.0), glm::Vec2::new(bounce.max_speed, 0.0));
+        game.update(0.1);
+        assert!(game.ball().velocity().norm() <= bounce.max_speed + 1e-4);
+    }
+
+    // Right paddle's bounding box after moving it to `y`.
+    fn right_paddle_at(game: &mut GameData, y: f32) -> Aabb {
+        game.players[1].paddle.move_y(y);
+        game.players[1].paddle.store_previous();
+        game.players[1].paddle.bounds()
+    }
+
+    #[test]
+    fn ball_bounces_off_paddle_top() {
+        let mut game = GameData::new(MatchConfig::new(2, MatchRules::default()));
+        let bounds = right_paddle_at(&mut game, 0.0);
+        let x = bounds.center().x;
+        launch(&mut game, glm::Vec2::new(x, 0.5), glm::Vec2::new(0.0, -1.0));
+        game.update(0.5);
+        assert!(game.ball().velocity().y > 0.0);
+        assert!(game.ball().position().y > bounds.max.y + game.ball().radius() - 1e-4);
+    }
+
+    #[test]
+    fn grazing_ball_misses_or_clips_the_corner() {
+        let mut game = GameData::new(MatchConfig::new(2, MatchRules::default()));
+        let bounds = right_paddle_at(&mut game, 0.0);
+        let radius = game.ball().radius();
+
+        // Skims just over the top: no contact, goes out for a point.
+        let clear = bounds.max.y + radius + 1e-3;
+        launch(&mut game, glm::Vec2::new(0.8, clear), glm::Vec2::new(1.0, 0.0));
+        game.update(0.5);
+        assert_eq!([1, 0], game.scores());
+
+        // Just low enough to catch the corner: comes back, kicked upwards.
+        let clip = bounds.max.y + radius * 0.5;
+        launch(&mut game, glm::Vec2::new(0.8, clip), glm::Vec2::new(1.0, 0.0));
+        game.update(0.2);
+        let velocity = game.ball().velocity();
+        assert!(velocity.x < 0.0 && velocity.y > 0.0);
+    }
+
+    #[test]
+    fn corner_impact_sends_ball_away_from_corner() {
+        let mut game = GameData::new(MatchConfig::new(2, MatchRules::default()));
+        let bounds = right_paddle_at(&mut game, 0.0);
+        // Coming down and right, straight at the top left corner.
+        let corner = glm::Vec2::new(bounds.min.x, bounds.max.y);
+        let start = corner + glm::Vec2::new(-0.2, 0.2);
+        launch(&mut game, start, glm::Vec2::new(1.0, -1.0));
+        game.update(0.3);
+        let velocity = game.ball().velocity();
+        assert!(velocity.x < 0.0 && velocity.y > 0.0);
+        assert_eq!([0, 0], game.scores());
+    }
+
+    #[test]
+    fn ball_behind_the_face_is_pushed_out() {
+        let mut game = GameData::new(MatchConfig::new(2, MatchRules::default()));
+        let bounds = right_paddle_at(&mut game, 0.0);
+        // Centre already past the face and still heading into the paddle.
+        launch(&mut game, glm::Vec2::new(bounds.min.x + 0.005, 0.0), glm::Vec2::new(1.0, 0.0));
+        game.update(DT);
+        assert!(!game.ball().shape().overlaps_aabb(&bounds.shrink(glm::Vec2::new(1e-4, 1e-4))));
+        assert!(game.ball().position().x < bounds.min.x);
+        assert!(game.ball().velocity().x < 0.0);
+        assert_eq!([0, 0], game.scores());
+    }
+
+    #[test]
+    fn paddle_moving_onto_ball_pushes_it() {
+        let mut game = GameData::new(MatchConfig::new(2, MatchRules::default()));
+        let bounds = right_paddle_at(&mut game, 0.0);
+        let x = bounds.center().x;
+        // Sitting just above the paddle, barely moving, while the paddle comes up underneath it.
+        let y = bounds.max.y + game.ball().radius() + 1e-3;
+        launch(&mut game, glm::Vec2::new(x, y), glm::Vec2::new(0.0, -0.01));
+        game.set_controller(1, Box::new(PaddleIntent::Up));
+        for _ in 0..10 {
+            game.update(DT);
+        }
+        let paddle_top = game.players()[1].paddle().bounds().max.y;
+        assert!(game.ball().velocity().y > 0.0);
+        assert!(game.ball().position().y >= paddle_top);
+    }
+
+    #[test]
+    fn headless_match_runs() {
+        let mut game = GameData::new(MatchConfig::default());
+        assert!(game.players().iter().all(Player::is_computer));
+        for _ in 0..(120 * 30) {
+            game.update(DT);
+            let pos = game.ball().position();
+            assert!(pos.x.is_finite() && pos.y.is_finite());
+        }
+    }
+}
@@ -0,0 +1,147 @@
+use super::COURT;
+use crate::physics::{Aabb, Surface};
+
+use nalgebra_glm as glm;
+
+#[derive(Clone, Copy, Debug)]
+pub struct Paddle {
+    id: u64,
+    pub(crate) width: f32,
+    pub(crate) height: f32,
+    pub(crate) position: glm::Vec2,
+    prev_position: glm::Vec2, // position at the start of the last tick, for interpolation
+    pub(crate) velocity: glm::Vec2,
+    motion: glm::Vec2, // how fast the paddle actually moved over the last tick
+}
+
+pub const X1_PADDLE: f32 = -0.015;
+pub const X2_PADDLE: f32 = 0.015;
+pub const Y1_PADDLE: f32 = -0.1;
+pub const Y2_PADDLE: f32 = 0.1;
+
+pub const PADDLE_WIDTH: f32 = X2_PADDLE - X1_PADDLE;
+pub const PADDLE_HEIGHT: f32 = Y2_PADDLE - Y1_PADDLE;
+
+impl Paddle {
+    pub fn new(id: u64, position: glm::Vec2, velocity: glm::Vec2, width: f32, height: f32) -> Self {
+        let mut paddle = Paddle {
+            id,
+            width,
+            height,
+            position,
+            prev_position: position,
+            velocity,
+            motion: glm::Vec2::zeros(),
+        };
+        paddle.clamp_position();
+        paddle.store_previous();
+        
+        paddle
+    }
+
+    pub fn reset(&mut self) {
+        self.move_y(0.0f32);
+        self.store_previous();
+        self.motion = glm::Vec2::zeros();
+    }
+
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    pub fn position(&self) -> &glm::Vec2 {
+        &self.position
+    }
+
+    /// Blends between where the paddle was at the start of the last tick and where it is now.
+    pub fn interpolated_position(&self, alpha: f32) -> glm::Vec2 {
+        glm::lerp(&self.prev_position, &self.position, alpha)
+    }
+
+    pub(crate) fn store_previous(&mut self) {
+        self.prev_position = self.position;
+    }
+
+    /// Velocity the paddle actually moved at over the last tick, zero when it stood still.
+    pub fn motion(&self) -> &glm::Vec2 {
+        &self.motion
+    }
+
+    pub(crate) fn update_motion(&mut self, delta: f32) {
+        if delta > 0.0f32 {
+            self.motion = (self.position - self.prev_position) / delta;
+        }
+    }
+
+    /// Where `point` sits along the paddle's height: -1 at the bottom, 0 in the middle and 1 at
+    /// the top.
+    pub fn hit_offset(&self, point: &glm::Vec2) -> f32 {
+        ((point.y - self.position.y) / (self.height / 2.0f32)).clamp(-1.0f32, 1.0f32)
+    }
+
+    pub fn width(&self) -> f32 {
+        self.width
+    }
+
+    pub fn height(&self) -> f32 {
+        self.height
+    }
+
+    /// Full speed up or down, in court units per second.
+    pub fn speed(&self) -> f32 {
+        self.velocity.y.abs()
+    }
+
+    pub fn apply_velocity(&mut self, delta: f32) {
+        self.position += self.velocity * delta;
+
+        self.clamp_position();
+    }
+
+    pub fn move_down(&mut self, delta: f32) {
+        if self.velocity.y > 0.0f32 {
+            self.velocity.y *= -1.0f32;
+        }
+        self.apply_velocity(delta);
+    }
+
+    pub fn move_up(&mut self, delta: f32) {
+        if self.velocity.y < 0.0f32 {
+            self.velocity.y *= -1.0f32;
+        }
+        self.apply_velocity(delta);
+    }
+
+    /// Moves at `axis` times full speed, up for positive values. `axis` is clamped to `[-1, 1]`.
+    pub fn move_axis(&mut self, axis: f32, delta: f32) {
+        self.position.y += self.speed() * axis.clamp(-1.0f32, 1.0f32) * delta;
+        self.clamp_position();
+    }
+
+    pub fn move_y(&mut self, y_pos: f32) {
+        self.move_position(glm::Vec2::new(self.position.x, y_pos));
+    }
+
+    pub fn move_position(&mut self, new_pos: glm::Vec2) {
+        self.position = new_pos;
+        self.clamp_position();
+    }
+
+    pub fn half_extents(&self) -> glm::Vec2 {
+        glm::Vec2::new(self.width / 2.0, self.height / 2.0)
+    }
+
+    pub fn bounds(&self) -> Aabb {
+        Aabb::from_center(self.position, self.half_extents())
+    }
+
+    fn clamp_position(&mut self) {
+        self.position = COURT.shrink(self.half_extents()).closest_point(&self.position);
+    }
+
+    /// All four sides of the paddle for collision. The sweep rounds off segment ends, so the
+    /// corners are covered as well.
+    pub fn surfaces(&self) -> [Surface; 4] {
+        self.bounds().edges()
+    }
+}
@@ -0,0 +1,75 @@
+use super::PaddleIntent;
+use super::controller::{MatchView, PaddleController};
+use super::paddle::Paddle;
+
+pub struct Player {
+    id: u32,
+    score: u32, // points in the current set
+    sets: u32,  // sets won so far in the match
+    pub(crate) paddle: Paddle,
+    controller: Box<dyn PaddleController>,
+}
+
+impl Player {
+    pub fn new(id: u32, paddle: Paddle, controller: Box<dyn PaddleController>) -> Self {
+        Player {
+            id,
+            score: 0u32,
+            sets: 0u32,
+            paddle,
+            controller,
+        }
+    }
+
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    pub fn score(&self) -> u32 {
+        self.score
+    }
+
+    pub fn sets(&self) -> u32 {
+        self.sets
+    }
+
+    pub fn paddle(&self) -> &Paddle {
+        &self.paddle
+    }
+
+    pub fn is_computer(&self) -> bool {
+        self.controller.is_computer()
+    }
+
+    pub fn set_controller(&mut self, controller: Box<dyn PaddleController>) {
+        self.controller = controller;
+    }
+
+    pub fn reset(&mut self) {
+        self.score = 0;
+        self.sets = 0;
+        self.paddle.reset();
+    }
+
+    pub fn reset_score(&mut self) {
+        self.score = 0;
+    }
+
+    pub fn increment_score(&mut self) {
+        self.score += 1;
+    }
+
+    pub fn increment_sets(&mut self) {
+        self.sets += 1;
+    }
+
+    /// Moves the paddle for this tick, wherever its controller wants it to go.
+    pub fn update(&mut self, delta: f32, view: &MatchView) {
+        match self.controller.intent(delta, view) {
+            PaddleIntent::Down => self.paddle.move_down(delta),
+            PaddleIntent::Up => self.paddle.move_up(delta),
+            PaddleIntent::Axis(axis) => self.paddle.move_axis(axis, delta),
+            PaddleIntent::Idle => { },
+        }
+    }
+}
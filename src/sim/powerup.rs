@@ -0,0 +1,93 @@
+// Arcade mode: power-ups that spawn on the court and the timed effects they hand out. A power-up
+// goes to whoever last hit the ball that runs into it.
+use crate::physics::Circle;
+
+use nalgebra_glm as glm;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum GameMode {
+    #[default]
+    Classic, // one ball, nothing on the court
+    Arcade,  // power-ups and multi-ball
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PowerUpKind {
+    Grow,       // the collector's paddle gets taller
+    Shrink,     // the opponent's paddle gets shorter
+    Split,      // the ball that collected it splits in two
+    SpeedBoost, // balls leave the collector's paddle faster
+    Sticky,     // balls stick to the collector's paddle for a moment before leaving it
+    Shield,     // a wall behind the collector's paddle saves one point
+}
+
+impl PowerUpKind {
+    pub const ALL: [PowerUpKind; 6] = [
+        PowerUpKind::Grow,
+        PowerUpKind::Shrink,
+        PowerUpKind::Split,
+        PowerUpKind::SpeedBoost,
+        PowerUpKind::Sticky,
+        PowerUpKind::Shield,
+    ];
+
+    /// Whether it applies to the opponent of whoever collected it.
+    pub fn hits_opponent(self) -> bool {
+        self == PowerUpKind::Shrink
+    }
+}
+
+/// Paddle height multiplier for `PowerUpKind::Grow` and `PowerUpKind::Shrink`.
+pub const GROW_FACTOR: f32 = 1.5;
+pub const SHRINK_FACTOR: f32 = 0.6;
+/// Speed multiplier for balls leaving a paddle with `PowerUpKind::SpeedBoost`, the ball's speed
+/// limit goes up by as much.
+pub const SPEED_BOOST: f32 = 1.4;
+/// How long a ball sticks to a paddle with `PowerUpKind::Sticky`, in seconds.
+pub const STICKY_HOLD: f32 = 0.5;
+/// Radius of a power-up on the court.
+pub const POWER_UP_RADIUS: f32 = 0.05;
+
+/// When and how long power-ups show up in `GameMode::Arcade`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PowerUpConfig {
+    pub spawn_interval: f32, // seconds of play between spawns
+    pub lifetime: f32,       // seconds a power-up stays on the court before it vanishes
+    pub duration: f32,       // seconds a timed effect lasts
+    pub max_on_court: usize,
+    pub max_balls: usize,    // splits stop once this many balls are in play
+}
+
+impl Default for PowerUpConfig {
+    fn default() -> Self {
+        PowerUpConfig {
+            spawn_interval: 6.0,
+            lifetime: 8.0,
+            duration: 10.0,
+            max_on_court: 2,
+            max_balls: 4,
+        }
+    }
+}
+
+/// A power-up waiting on the court.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PowerUp {
+    pub kind: PowerUpKind,
+    pub position: glm::Vec2,
+    pub time_left: f32,
+}
+
+impl PowerUp {
+    pub fn shape(&self) -> Circle {
+        Circle::new(self.position, POWER_UP_RADIUS)
+    }
+}
+
+/// A timed power-up working on `player`'s side.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Effect {
+    pub player: usize,
+    pub kind: PowerUpKind,
+    pub time_left: f32,
+}
@@ -0,0 +1,94 @@
+// Match rules: how many points win a set, how many sets win the match, and who serves.
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ServeRotation {
+    ToLoser,    // whoever won the point serves the next one, at whoever lost it
+    EveryPoint, // serve swaps after every point
+    EverySet,   // one player serves a whole set, then it swaps
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MatchRules {
+    pub target_score: u32, // points needed to take a set
+    pub win_by: u32,       // lead needed on top of reaching the target, 2 for "win by 2"
+    pub best_of: u32,      // sets in the series, 1 for a single game
+    pub serve_rotation: ServeRotation,
+}
+
+impl Default for MatchRules {
+    // First to 11, win by 2, one set, served at whoever lost the last point.
+    fn default() -> Self {
+        MatchRules {
+            target_score: 11,
+            win_by: 2,
+            best_of: 1,
+            serve_rotation: ServeRotation::ToLoser,
+        }
+    }
+}
+
+impl MatchRules {
+    pub fn first_to(target_score: u32, win_by: u32) -> Self {
+        MatchRules {
+            target_score,
+            win_by,
+            ..Default::default()
+        }
+    }
+
+    pub fn best_of(mut self, sets: u32) -> Self {
+        self.best_of = sets;
+        self
+    }
+
+    pub fn serve_rotation(mut self, rotation: ServeRotation) -> Self {
+        self.serve_rotation = rotation;
+        self
+    }
+
+    /// Sets a player has to win to take the match.
+    pub fn sets_to_win(&self) -> u32 {
+        self.best_of.max(1) / 2 + 1
+    }
+
+    /// Index of the player that has won the current set with these `scores`, if any.
+    pub fn set_winner(&self, scores: [u32; 2]) -> Option<usize> {
+        let win_by = self.win_by.max(1);
+        for (player, opponent) in [(0, 1), (1, 0)] {
+            if scores[player] >= self.target_score && scores[player] >= scores[opponent] + win_by {
+                return Some(player);
+            }
+        }
+        None
+    }
+
+    /// Index of the player that has won the match with these set counts, if any.
+    pub fn match_winner(&self, sets: [u32; 2]) -> Option<usize> {
+        sets.iter().position(|won| *won >= self.sets_to_win())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn first_to_eleven_win_by_two() {
+        let rules = MatchRules::default();
+        assert_eq!(None, rules.set_winner([10, 9]));
+        assert_eq!(Some(0), rules.set_winner([11, 9]));
+        assert_eq!(None, rules.set_winner([11, 10]));
+        assert_eq!(None, rules.set_winner([12, 11]));
+        assert_eq!(Some(1), rules.set_winner([12, 14]));
+    }
+
+    #[test]
+    fn best_of_series() {
+        let rules = MatchRules::first_to(5, 1).best_of(5);
+        assert_eq!(3, rules.sets_to_win());
+        assert_eq!(Some(0), rules.set_winner([5, 4]));
+        assert_eq!(None, rules.match_winner([2, 2]));
+        assert_eq!(Some(1), rules.match_winner([1, 3]));
+        assert_eq!(1, MatchRules::default().sets_to_win());
+    }
+}